@@ -0,0 +1,75 @@
+//! Helpers shared by the benchmarks : a deterministic random generator, the generated maps and
+//! timing.
+#![allow(dead_code)]
+use std::time::Instant;
+
+use demo::geometry::Point;
+
+/// A linear congruential generator, to draw the same points on every run
+pub struct Lcg(pub u64);
+
+impl Lcg {
+
+    pub fn next(&mut self, bound : i32) -> i32 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as i32
+    }
+}
+
+/// How the dust is spread on a generated map
+#[derive(Clone, Copy, Debug)]
+pub enum Density {
+    /// One tile out of a thousand, spread uniformly
+    Sparse,
+    /// One tile out of two, spread uniformly
+    Dense,
+    /// As many tiles as `Sparse`, gathered in a few small clusters
+    Clustered,
+}
+
+impl Density {
+
+    /// Returns the side of a square map holding `count` dust tiles
+    pub fn side(self, count : usize) -> i32 {
+        let cells = match self {
+            Density::Sparse | Density::Clustered => count * 1000,
+            Density::Dense => count * 2,
+        };
+        (cells as f64).sqrt().ceil() as i32
+    }
+
+    /// Draws at most `count` distinct dust tiles on a square map of side `self.side(count)`
+    pub fn points(self, count : usize, lcg : &mut Lcg) -> Vec<Point<i32>> {
+        let side = self.side(count);
+        let mut points = match self {
+            Density::Sparse | Density::Dense => {
+                (0..count).map(|_| Point::new(lcg.next(side), lcg.next(side))).collect::<Vec<_>>()
+            },
+            Density::Clustered => {
+                // 16 clusters, each a square with a fifth of the density of the dense maps
+                let clusters = 16;
+                let radius = ((count * 10 / clusters) as f64).sqrt() as i32 / 2 + 1;
+                let centers = (0..clusters).map(|_| {
+                    Point::new(radius + lcg.next(side - 2 * radius), radius + lcg.next(side - 2 * radius))
+                }).collect::<Vec<_>>();
+                (0..count).map(|i| {
+                    let c = centers[i % clusters];
+                    Point::new(c.get_x() + lcg.next(2 * radius) - radius, c.get_y() + lcg.next(2 * radius) - radius)
+                }).collect()
+            },
+        };
+        points.sort_by_key(|p| (p.get_x(), p.get_y()));
+        points.dedup();
+        points
+    }
+}
+
+/// Calls `f` and returns its result along with the number of operations per second, `count`
+/// being the number of operations done by `f`
+pub fn rate<R, F : FnOnce() -> R>(count : usize, f : F) -> (R, f64) {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+    (result, count as f64 / seconds)
+}
@@ -0,0 +1,138 @@
+//! Measures `RTree` and `GameMap` operations across map sizes, dust densities and radix values,
+//! and compares them to the `Grid` spatial index. Run with `cargo bench --bench scale`.
+//!
+//! The grid stores a slot per tile, so it is skipped on maps with more than `GRID_CELLS` tiles. The depths of the trees built by insertion and by bulk loading are reported along with
+//! the rates.
+extern crate demo;
+
+mod common;
+
+use demo::game::{Backend, GameMap};
+use demo::geometry::{Point, Tile};
+use demo::index::{Grid, SpatialIndex};
+use demo::parser::RoverMove;
+use demo::rtree::RTree;
+
+use common::{Density, Lcg, rate};
+
+/// Largest number of tiles of a map stored in a grid
+const GRID_CELLS : usize = 1 << 28;
+
+/// Number of probes of the `find` measures
+const PROBES : usize = 200_000;
+
+/// Number of moves of the rover path
+const MOVES : usize = 1_000_000;
+
+/// Returns true if a square map of the given side is small enough to be stored in a grid
+fn fits_grid(side : i32) -> bool {
+    side as usize * side as usize <= GRID_CELLS
+}
+
+/// Formats a rate, or a dash if it was not measured
+fn show(rate : Option<f64>) -> String {
+    rate.map(|r| format!("{:.0}", r)).unwrap_or_else(|| "-".to_string())
+}
+
+/// Measures insertion, `find` and `find_mut` on trees of several radix values
+fn tree_operations(densities : &[Density], counts : &[usize]) {
+    println!("{:>9} {:>9} {:>7} {:>6} {:>12} {:>6} {:>6} {:>12} {:>12} {:>12}",
+             "density", "points", "side", "radix", "insert/s", "depth", "bulk", "find/s", "find_mut/s", "grid find/s");
+    for &density in densities {
+        for &count in counts {
+            let side = density.side(count);
+            let mut lcg = Lcg(count as u64);
+            let points = density.points(count, &mut lcg);
+            // Half of the probes are dust tiles, the other half are random tiles
+            let mut probes = Vec::with_capacity(PROBES);
+            for i in 0..PROBES / 2 {
+                probes.push(points[(i * 7919) % points.len()]);
+                probes.push(Point::new(lcg.next(side), lcg.next(side)));
+            }
+            let grid = if fits_grid(side) {
+                let mut grid = Grid::new(Tile::new(Point::new(0, 0), Point::new(side - 1, side - 1))).unwrap();
+                for &p in &points {
+                    grid.insert(p, ());
+                }
+                Some(grid)
+            } else {
+                None
+            };
+
+            for &radix in &[4, 8, 16] {
+                let (inserted, insert) = rate(points.len(), || {
+                    let mut rtree = RTree::with_radix(radix);
+                    for &p in &points {
+                        rtree.insert(p, 0u32);
+                    }
+                    rtree
+                });
+
+                let mut rtree = RTree::bulk_load_with_radix(radix, points.iter().map(|&p| (p, 0u32)).collect());
+                let (hits, find) = rate(probes.len(), || probes.iter().filter(|&&p| rtree.find(p).is_some()).count());
+                let (_, find_mut) = rate(probes.len(), || {
+                    for &p in &probes {
+                        if let Some(data) = rtree.find_mut(p) {
+                            *data += 1;
+                        }
+                    }
+                });
+                let grid_find = grid.as_ref().map(|grid| {
+                    let (grid_hits, grid_find) = rate(probes.len(), || probes.iter().filter(|&&p| grid.find(p).is_some()).count());
+                    assert_eq!(hits, grid_hits);
+                    grid_find
+                });
+
+                println!("{:>9} {:>9} {:>7} {:>6} {:>12.0} {:>6} {:>6} {:>12.0} {:>12.0} {:>12}",
+                         format!("{:?}", density), points.len(), side, radix, insert, inserted.stats().depth,
+                         rtree.stats().depth, find, find_mut, show(grid_find));
+            }
+        }
+    }
+}
+
+/// Measures a random walk of the rover on game maps storing the dust in an `RTree` and in a grid
+fn rover_path(densities : &[Density], counts : &[usize]) {
+    println!("{:>9} {:>9} {:>7} {:>12} {:>12} {:>12}",
+             "density", "points", "side", "cleaned", "moves/s", "grid moves/s");
+    for &density in densities {
+        for &count in counts {
+            let side = density.side(count);
+            let mut lcg = Lcg(count as u64);
+            let points = density.points(count, &mut lcg);
+            let dust = points.len();
+            let rover = Point::new(side / 2, side / 2);
+            let moves = (0..MOVES).map(|_| match lcg.next(4) {
+                0 => RoverMove::North,
+                1 => RoverMove::East,
+                2 => RoverMove::South,
+                _ => RoverMove::West,
+            }).collect::<Vec<_>>();
+
+            let top = Point::new(side - 1, side - 1);
+            let mut grid = if fits_grid(side) {
+                Some(GameMap::with_backend(Backend::Grid, top, rover, points.clone()).unwrap())
+            } else {
+                None
+            };
+            let mut game = GameMap::with_backend(Backend::RTree, top, rover, points).unwrap();
+            let (cleaned, moves_rate) = rate(moves.len(), || game.move_rover_path(&moves));
+            let grid_rate = grid.as_mut().map(|grid| {
+                let (grid_cleaned, grid_rate) = rate(moves.len(), || grid.move_rover_path(&moves));
+                assert_eq!(cleaned, grid_cleaned);
+                grid_rate
+            });
+
+            println!("{:>9} {:>9} {:>7} {:>12} {:>12.0} {:>12}",
+                     format!("{:?}", density), dust, side, cleaned, moves_rate, show(grid_rate));
+        }
+    }
+}
+
+fn main() {
+    let densities = [Density::Sparse, Density::Dense, Density::Clustered];
+    let counts = [1_000, 10_000, 100_000, 1_000_000];
+    tree_operations(&densities, &counts);
+    println!("");
+    rover_path(&densities, &counts);
+}
@@ -1,66 +1,124 @@
 use std::mem;
 
 use ::rtree::RTree;
+use ::index::{
+    Grid,
+    SpatialIndex,
+};
 use ::parser::{
     ParseError,
     RoverMove,
 };
 use ::geometry::{
+    Coordinate,
     Tile,
     Point,
+    Contains,
 };
 
-pub struct GameMap {
-    rover : Point<i32>,
-    dust_map : RTree<i32, Entity>,
-    grid_top : Point<i32>,
+/// Maps with at most this many tiles are stored in a grid
+const SMALL_MAP_TILES : u64 = 1 << 16;
+
+/// Maps with at most this many tiles per dust tile are dense, and stored in a grid
+const DENSE_MAP_RATIO : u64 = 16;
+
+/// The spatial index storing the dust of a map
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// A grid for small or dense maps, an `RTree` otherwise
+    Auto,
+    /// An `RTree`, suited to large maps with sparse dust
+    RTree,
+    /// A `Grid` with a slot per tile, suited to small or dense maps
+    Grid,
+}
+
+/// A map of a rectangular arena, starting at the origin. Any coordinate type can be used, the
+/// rover stays on the arena without overflowing the coordinates.
+pub struct GameMap<Coord : Coordinate = i32> {
+    rover : Point<Coord>,
+    dust_map : Box<SpatialIndex<Coord, Entity>>,
+    backend : Backend,
+    arena : Tile<Coord>,
 }
 
-impl GameMap {
+impl<Coord : Coordinate> GameMap<Coord> {
 
-    pub fn rover_pos(&self) -> Point<i32> {
+    pub fn rover_pos(&self) -> Point<Coord> {
         self.rover
     }
 
-    pub fn new(grid_top : Point<i32>, rover : Point<i32>, dust : Vec<Point<i32>>) -> Result<GameMap, ParseError>
+    /// Returns the spatial index storing the dust, never `Backend::Auto`
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Creates a map, storing the dust in a grid if the map is small or dense and in an `RTree`
+    /// otherwise
+    pub fn new(grid_top : Point<Coord>, rover : Point<Coord>, dust : Vec<Point<Coord>>) -> Result<GameMap<Coord>, ParseError> {
+        GameMap::with_backend(Backend::Auto, grid_top, rover, dust)
+    }
+
+    /// Creates a map storing the dust in the given spatial index
+    pub fn with_backend(backend : Backend, grid_top : Point<Coord>, rover : Point<Coord>, dust : Vec<Point<Coord>>) -> Result<GameMap<Coord>, ParseError>
     {
-        let arena = Tile::new(Point::new(0, 0), grid_top); 
+        let arena = try!(Tile::try_new(Point::new(Coord::zero(), Coord::zero()), grid_top).map_err(ParseError::InvalidArena));
 
         // Checks that the rover is on the map
-        if rover <= arena {
-            let mut rtree = RTree::<i32, Entity>::new();
+        if arena.contains(&rover) {
+            let mut entries = Vec::with_capacity(dust.len());
 
             for p in dust {
-                if p <= arena { 
-                    rtree.insert(p, Entity::dust());
+                if arena.contains(&p) { 
+                    entries.push((p, Entity::dust()));
                 } else {
                     return Err(ParseError::InvalidDustPosition);
                 }
             }
+            let backend = match backend {
+                Backend::Auto => {
+                    let tiles = arena.area();
+                    if tiles <= SMALL_MAP_TILES as f64 || tiles <= (DENSE_MAP_RATIO * entries.len() as u64) as f64 {
+                        Backend::Grid
+                    } else {
+                        Backend::RTree
+                    }
+                },
+                backend => backend,
+            };
+            let dust_map : Box<SpatialIndex<Coord, Entity>> = match backend {
+                Backend::Grid => {
+                    let mut grid = try!(Grid::new(arena).map_err(ParseError::ArenaTooLarge));
+                    for (p, entity) in entries {
+                        grid.insert(p, entity);
+                    }
+                    Box::new(grid)
+                },
+                _ => Box::new(RTree::bulk_load(entries)),
+            };
             Ok(GameMap {
                 rover : rover,
-                dust_map : rtree,
-                grid_top : grid_top,
+                dust_map : dust_map,
+                backend : backend,
+                arena : arena,
             })
         } else {
             Err(ParseError::InvalidRoverPosition)
         }
     }
 
-    /// Moves the rover into the given direction
+    /// Moves the rover into the given direction, unless it would leave the arena
     #[inline]
-    fn move_rover(&mut self, dir : RoverMove) -> Point<i32> {
-        let vector = dir.as_vector();
-        let new_pos = self.rover + vector;
-        if new_pos <= self.grid_top && new_pos >= Point::new(0, 0) { 
-            self.rover = self.rover + vector;
-        } 
+    fn move_rover(&mut self, dir : RoverMove) -> Point<Coord> {
+        if let Some(new_pos) = dir.checked_move(self.rover).filter(|p| self.arena.contains(p)) {
+            self.rover = new_pos;
+        }
         self.rover
     }
 
     /// Moves the rover along the given path and returns the cleaned dust tiles
     pub fn move_rover_path(&mut self, moves : &[RoverMove]) -> usize {
-        let mut map = RTree::<i32, Entity>::new();
+        let mut map : Box<SpatialIndex<Coord, Entity>> = Box::new(RTree::new());
         mem::swap(&mut map, &mut self.dust_map);
         let count = 
         moves.iter()
@@ -68,7 +126,7 @@ impl GameMap {
             .map(|d| self.move_rover(d))
             // Uncomment to print the rover path
             // .inspect(|pos| println!("{:?}", pos))
-            .filter(|pos| map.find_mut(*pos).map(|d| d.clean_dust()).unwrap_or(false))
+            .filter(|pos| map.remove(*pos).map(|d| d.dust).unwrap_or(false))
             .count();
         mem::swap(&mut map, &mut self.dust_map);
         count
@@ -84,6 +142,18 @@ impl RoverMove {
             RoverMove::West => Point::new(-1, 0),
         }
     }
+
+    /// Returns the neighbour of a point in the direction of the move, or `None` if a coordinate
+    /// overflows. Unlike `as_vector`, works with unsigned coordinates.
+    pub fn checked_move<Coord : Coordinate>(self, p : Point<Coord>) -> Option<Point<Coord>> {
+        let (zero, one) = (Coord::zero(), Coord::one());
+        match self {
+            RoverMove::North => p.checked_add(Point::new(zero, one)),
+            RoverMove::South => p.checked_sub(Point::new(zero, one)),
+            RoverMove::East => p.checked_add(Point::new(one, zero)),
+            RoverMove::West => p.checked_sub(Point::new(one, zero)),
+        }
+    }
 }
 
 /// A game tile
@@ -100,11 +170,67 @@ impl Entity {
             dust : true,
         }
     }
+}
 
-    fn clean_dust(&mut self) -> bool  {
-        let is_dust = self.dust;
-        self.dust = false;
-        is_dust
+
+#[cfg(test)]
+mod test {
+    use ::geometry::{Point, GeometryError};
+    use ::parser::{RoverMove, ParseError};
+    use ::index::GridError;
+    use super::{Backend, GameMap};
+
+    #[test]
+    fn backends() {
+        let dust = vec![Point::new(1, 0), Point::new(2, 2), Point::new(3, 2), Point::new(9, 9)];
+        let moves = [RoverMove::East, RoverMove::North, RoverMove::North, RoverMove::East,
+                     RoverMove::East, RoverMove::South, RoverMove::South, RoverMove::South];
+        for &backend in &[Backend::Auto, Backend::RTree, Backend::Grid] {
+            let mut game = GameMap::with_backend(backend, Point::new(9, 9), Point::new(0, 0), dust.clone()).unwrap();
+            assert_eq!(game.move_rover_path(&moves), 3);
+            assert_eq!(game.rover_pos(), Point::new(3, 0));
+        }
+
+        // Small maps and large dense maps use a grid, large sparse maps an RTree
+        let small = GameMap::new(Point::new(9, 9), Point::new(0, 0), dust.clone()).unwrap();
+        assert_eq!(small.backend(), Backend::Grid);
+        let sparse = GameMap::new(Point::new(999, 999), Point::new(0, 0), dust).unwrap();
+        assert_eq!(sparse.backend(), Backend::RTree);
+        let dense = (0..1000).flat_map(|x| (0..100).map(move |y| Point::new(x, y))).collect();
+        let dense = GameMap::new(Point::new(999, 999), Point::new(0, 0), dense).unwrap();
+        assert_eq!(dense.backend(), Backend::Grid);
+    }
+
+    #[test]
+    fn coordinate_bounds() {
+        let moves = [RoverMove::West, RoverMove::South, RoverMove::East, RoverMove::North, RoverMove::North];
+        // The rover stays at the origin of an unsigned map
+        let mut game = GameMap::new(Point::new(1u8, 255), Point::new(0, 0), vec![Point::new(1, 1)]).unwrap();
+        assert_eq!(game.move_rover_path(&moves), 1);
+        assert_eq!(game.rover_pos(), Point::new(1, 2));
+
+        // And at the top of a map as large as the coordinate type
+        let max = i32::max_value();
+        for &backend in &[Backend::RTree, Backend::Auto] {
+            let mut game = GameMap::with_backend(backend, Point::new(max, max), Point::new(max, max), vec![Point::new(max - 1, max)]).unwrap();
+            assert_eq!(game.move_rover_path(&[RoverMove::North, RoverMove::East, RoverMove::West]), 1);
+            assert_eq!(game.rover_pos(), Point::new(max - 1, max));
+        }
+
+        // But cannot be stored in a grid
+        let max = u64::max_value();
+        match GameMap::with_backend(Backend::Grid, Point::new(max, max), Point::new(0, 0), vec![]) {
+            Err(ParseError::ArenaTooLarge(GridError::TooLarge)) => (),
+            other => panic!("unexpected result {:?}", other.map(|game| game.rover_pos())),
+        }
     }
-}
 
+    #[test]
+    fn empty_arena() {
+        // A 0 0 grid has its top right corner below the origin
+        match GameMap::new(Point::new(-1, -1), Point::new(0, 0), vec![]) {
+            Err(ParseError::InvalidArena(GeometryError::InvertedCorners)) => (),
+            other => panic!("unexpected result {:?}", other.map(|game| game.rover_pos())),
+        }
+    }
+}
@@ -14,12 +14,18 @@ use std::cmp::{
     min,
     max,
 };
-use std::fmt::{Display, Debug};
-use ::num::traits::{One, Zero};
+use std::fmt::{
+    self,
+    Display,
+    Debug,
+};
+use std::error::Error;
+use ::num::traits::{One, Zero, ToPrimitive, CheckedAdd, CheckedSub, Saturating};
 
-/// A marker trait for an axis coordinate representation
-pub trait Coordinate : Debug + Display + Eq + Ord + PartialOrd + Clone + Copy + One + Zero +
-Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + 'static { }
+/// A marker trait for an axis coordinate representation. The checked and saturating operations
+/// let points move near the bounds of the coordinate type without overflowing.
+pub trait Coordinate : Debug + Display + Eq + Ord + PartialOrd + Clone + Copy + One + Zero + ToPrimitive +
+Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + CheckedAdd + CheckedSub + Saturating + 'static { }
 
 impl Coordinate for usize {}
 impl Coordinate for u64 {}
@@ -62,10 +68,72 @@ impl<Coord : Coordinate> Point<Coord> {
         self.y.cmp(&rhs.y)
     }
 
+    /// Returns the distance between two points moving along the axes : the sum of the distances
+    /// on each axis. The distance is computed in `u128`, so it is exact for every coordinate type.
+    pub fn manhattan_distance(self, rhs : Point<Coord>) -> u128 {
+        axis_distance(self.x, rhs.x) + axis_distance(self.y, rhs.y)
+    }
+
+    /// Returns the distance between two points moving along the axes and the diagonals : the
+    /// greatest of the distances on each axis. The distance is exact for every coordinate type.
+    pub fn chebyshev_distance(self, rhs : Point<Coord>) -> u128 {
+        max(axis_distance(self.x, rhs.x), axis_distance(self.y, rhs.y))
+    }
+
+    /// Returns the square of the euclidean distance between two points, which orders points by
+    /// euclidean distance without computing square roots.
+    ///
+    /// The square is computed in `u128`, so it is exact for coordinates of up to 32 bits, and for
+    /// 64 bits coordinates closer than 2^63 on one of the axes. Farther 64 bits coordinates
+    /// saturate to `u128::MAX`.
+    pub fn squared_distance(self, rhs : Point<Coord>) -> u128 {
+        let (dx, dy) = (axis_distance(self.x, rhs.x), axis_distance(self.y, rhs.y));
+        (dx * dx).saturating_add(dy * dy)
+    }
+
+    /// Vector addition, returning `None` if a coordinate overflows
+    pub fn checked_add(self, rhs : Point<Coord>) -> Option<Point<Coord>> {
+        match (self.x.checked_add(&rhs.x), self.y.checked_add(&rhs.y)) {
+            (Some(x), Some(y)) => Some(Point { x : x, y : y }),
+            _ => None,
+        }
+    }
+
+    /// Vector subtraction, returning `None` if a coordinate overflows
+    pub fn checked_sub(self, rhs : Point<Coord>) -> Option<Point<Coord>> {
+        match (self.x.checked_sub(&rhs.x), self.y.checked_sub(&rhs.y)) {
+            (Some(x), Some(y)) => Some(Point { x : x, y : y }),
+            _ => None,
+        }
+    }
+
+    /// Vector addition, each coordinate being clamped to the bounds of the coordinate type
+    pub fn saturating_add(self, rhs : Point<Coord>) -> Point<Coord> {
+        Point {
+            x : self.x.saturating_add(rhs.x),
+            y : self.y.saturating_add(rhs.y),
+        }
+    }
+
+    /// Vector subtraction, each coordinate being clamped to the bounds of the coordinate type
+    pub fn saturating_sub(self, rhs : Point<Coord>) -> Point<Coord> {
+        Point {
+            x : self.x.saturating_sub(rhs.x),
+            y : self.y.saturating_sub(rhs.y),
+        }
+    }
+
     pub fn get_x(&self) -> Coord { self.x }
     pub fn get_y(&self) -> Coord { self.y }
 }
 
+/// Returns the distance between two coordinates of an axis. The difference is computed in `i128`,
+/// which holds the difference of any two values of a coordinate type up to 64 bits.
+fn axis_distance<Coord : Coordinate>(a : Coord, b : Coord) -> u128 {
+    let (a, b) = (a.to_i128().unwrap(), b.to_i128().unwrap());
+    a.abs_diff(b)
+}
+
 impl<Coord : Coordinate> PartialEq for Point<Coord> {
     /// Compares the coordinates and returns true if the two points represent the same location in
     /// the 2D plane
@@ -78,10 +146,20 @@ impl<Coord : Coordinate> PartialEq for Point<Coord> {
 /// equality
 impl<Coord : Coordinate> Eq for Point<Coord> { }
 
+/// Writes the coordinates separated by a space, as in the game input format
+impl<Coord : Coordinate> Display for Point<Coord> {
+
+    fn fmt(&self, f : &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{} {}", self.get_x(), self.get_y())
+    }
+}
+
 impl<Coord : Coordinate> Sub<Point<Coord>> for Point<Coord> {
     type Output = Point<Coord>;
 
     /// Vector subtraction. Returns a point translated by the given vector
+    ///
+    /// Overflows like the coordinates, see `checked_sub` and `saturating_sub`
     fn sub(self, rhs : Point<Coord>) -> Point<Coord> {
         Point {
             x : self.x - rhs.x,
@@ -94,6 +172,8 @@ impl<Coord : Coordinate> Add<Point<Coord>> for Point<Coord> {
     type Output = Point<Coord>;
 
     /// Vector addition. Returns a point translated by the given vector
+    ///
+    /// Overflows like the coordinates, see `checked_add` and `saturating_add`
     fn add(self, rhs : Point<Coord>) -> Point<Coord> {
         Point {
             x : self.x + rhs.x,
@@ -290,7 +370,7 @@ impl<Coord : Coordinate> PartialEq<Point<Coord>> for HorizontalLine<Coord> {
     }
 }
 
-pub trait Line<Coord> : Debug {
+pub trait Line<Coord : Coordinate> : Debug {
     fn is_vertical(&self) -> bool {false }
     fn is_horizontal(&self) -> bool { false}
 
@@ -299,6 +379,13 @@ pub trait Line<Coord> : Debug {
     fn cmp_with_tile(&self, rhs : &Tile<Coord>) -> Option<Ordering>;
     fn cmp_with_point(&self, rhs : &Point<Coord>) -> Option<Ordering>;
 
+    /// Returns the side of the line the tile is on, see `SideOf`
+    fn tile_side(&self, rhs : &Tile<Coord>) -> Side;
+    /// Returns the side of the line the point is on, see `SideOf`
+    fn point_side(&self, rhs : &Point<Coord>) -> Side;
+
+    /// Splits a tile into the part on or before the line and the part strictly after it
+    fn clip_tile(&self, rhs : &Tile<Coord>) -> (Option<Tile<Coord>>, Option<Tile<Coord>>);
 }
 
 impl<Coord : Coordinate> Line<Coord> for VerticalLine<Coord> {
@@ -312,6 +399,24 @@ impl<Coord : Coordinate> Line<Coord> for VerticalLine<Coord> {
         self.partial_cmp(rhs)
     }
 
+    fn tile_side(&self, rhs : &Tile<Coord>) -> Side {
+        rhs.side_of(self)
+    }
+
+    fn point_side(&self, rhs : &Point<Coord>) -> Side {
+        rhs.side_of(self)
+    }
+
+    fn clip_tile(&self, rhs : &Tile<Coord>) -> (Option<Tile<Coord>>, Option<Tile<Coord>>) {
+        if self.x < rhs.bottom.x {
+            (None, Some(*rhs))
+        } else if self.x >= rhs.top.x {
+            (Some(*rhs), None)
+        } else {
+            (Some(Tile { bottom : rhs.bottom, top : Point { x : self.x, y : rhs.top.y } }),
+             Some(Tile { bottom : Point { x : self.x + Coord::one(), y : rhs.bottom.y }, top : rhs.top }))
+        }
+    }
 }
 impl<Coord : Coordinate> Line<Coord> for HorizontalLine<Coord> {
     fn is_horizontal(&self) -> bool { true }
@@ -323,6 +428,25 @@ impl<Coord : Coordinate> Line<Coord> for HorizontalLine<Coord> {
     fn cmp_with_point(&self, rhs : &Point<Coord>) -> Option<Ordering> {
         self.partial_cmp(rhs)
     }
+
+    fn tile_side(&self, rhs : &Tile<Coord>) -> Side {
+        rhs.side_of(self)
+    }
+
+    fn point_side(&self, rhs : &Point<Coord>) -> Side {
+        rhs.side_of(self)
+    }
+
+    fn clip_tile(&self, rhs : &Tile<Coord>) -> (Option<Tile<Coord>>, Option<Tile<Coord>>) {
+        if self.y < rhs.bottom.y {
+            (None, Some(*rhs))
+        } else if self.y >= rhs.top.y {
+            (Some(*rhs), None)
+        } else {
+            (Some(Tile { bottom : rhs.bottom, top : Point { x : rhs.top.x, y : self.y } }),
+             Some(Tile { bottom : Point { x : rhs.bottom.x, y : self.y + Coord::one() }, top : rhs.top }))
+        }
+    }
 }
 
 impl<'a, Coord : Coordinate> PartialOrd<Point<Coord>> for (Line<Coord> + 'a) {
@@ -369,12 +493,29 @@ impl<Coord : Coordinate> Tile<Coord> {
     ///
     /// # panic
     ///
-    /// Panics if `bottom` is upper or right to `top`
+    /// Panics if `bottom` is upper or right to `top`, see `try_new` and `from_corners`
     pub fn new(bottom : Point<Coord>, top : Point<Coord>) -> Tile<Coord> {
-        assert!(bottom <= top);
+        Tile::try_new(bottom, top).unwrap()
+    }
+
+    /// Creates a new `Tile` given its bottom left corner and its top right corner, or returns
+    /// `GeometryError::InvertedCorners` if `bottom` is upper or right to `top`
+    pub fn try_new(bottom : Point<Coord>, top : Point<Coord>) -> Result<Tile<Coord>, GeometryError> {
+        if bottom.x <= top.x && bottom.y <= top.y {
+            Ok(Tile {
+                bottom : bottom,
+                top : top
+            })
+        } else {
+            Err(GeometryError::InvertedCorners)
+        }
+    }
+
+    /// Creates the smallest `Tile` containing two opposite corners, given in any order
+    pub fn from_corners(p1 : Point<Coord>, p2 : Point<Coord>) -> Tile<Coord> {
         Tile {
-            bottom : bottom,
-            top : top
+            bottom : Point::new(min(p1.x, p2.x), min(p1.y, p2.y)),
+            top : Point::new(max(p1.x, p2.x), max(p1.y, p2.y)),
         }
     }
 
@@ -443,6 +584,174 @@ impl<Coord : Coordinate> Tile<Coord> {
             }
         }
     }
+
+    /// Returns the tile shared by two tiles, or `None` if they do not overlap
+    pub fn intersection(&self, rhs : &Tile<Coord>) -> Option<Tile<Coord>> {
+        if self.intersects(rhs) {
+            Some(Tile {
+                bottom : Point {
+                    x : max(self.bottom.x, rhs.bottom.x),
+                    y : max(self.bottom.y, rhs.bottom.y),
+                },
+                top : Point {
+                    x : min(self.top.x, rhs.top.x),
+                    y : min(self.top.y, rhs.top.y),
+                },
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the disjoint tiles covering the points of the tile which are not in `rhs`, at most
+    /// four : the bands below and above `rhs`, then the parts left and right of `rhs` between
+    /// these bands.
+    pub fn difference(&self, rhs : &Tile<Coord>) -> Vec<Tile<Coord>> {
+        let common =
+            match self.intersection(rhs) {
+                Some(common) => common,
+                None => return vec![*self],
+            };
+        let (bottom, top) = (self.bottom, self.top);
+        let (inner_bottom, inner_top) = (common.bottom, common.top);
+        let mut parts = Vec::with_capacity(4);
+
+        if inner_bottom.y > bottom.y {
+            parts.push(Tile::new(bottom, Point::new(top.x, inner_bottom.y - Coord::one())));
+        }
+        if inner_top.y < top.y {
+            parts.push(Tile::new(Point::new(bottom.x, inner_top.y + Coord::one()), top));
+        }
+        if inner_bottom.x > bottom.x {
+            parts.push(Tile::new(Point::new(bottom.x, inner_bottom.y), Point::new(inner_bottom.x - Coord::one(), inner_top.y)));
+        }
+        if inner_top.x < top.x {
+            parts.push(Tile::new(Point::new(inner_top.x + Coord::one(), inner_bottom.y), Point::new(top.x, inner_top.y)));
+        }
+        parts
+    }
+
+    /// Returns true if every point of `rhs` is in the tile. This is `Contains::contains`, to which
+    /// it delegates, under a name which does not need the trait in scope.
+    pub fn contains_tile(&self, rhs : &Tile<Coord>) -> bool {
+        self.contains(rhs)
+    }
+
+    /// Returns the tile grown by `margin` on every side, the corners being clamped to the bounds
+    /// of the coordinate type
+    pub fn expand_by(&self, margin : Coord) -> Tile<Coord> {
+        let margin = Point::new(margin, margin);
+        Tile {
+            bottom : self.bottom.saturating_sub(margin),
+            top : self.top.saturating_add(margin),
+        }
+    }
+
+    /// Returns the number of columns of points in the tile, one for a vertical segment. The
+    /// measures of a tile count points, seen as unit cells, so that they are never null.
+    pub fn width(&self) -> u128 {
+        axis_distance(self.bottom.x, self.top.x) + 1
+    }
+
+    /// Returns the number of rows of points in the tile, one for an horizontal segment
+    pub fn height(&self) -> u128 {
+        axis_distance(self.bottom.y, self.top.y) + 1
+    }
+
+    /// Returns the number of points in the tile, one for a tile reduced to a point. The area is
+    /// a floating point number since the product of the width and height may overflow.
+    pub fn area(&self) -> f64 {
+        self.width() as f64 * self.height() as f64
+    }
+
+    /// Returns the perimeter of the tile as a floating point number, the sides of its cells
+    /// included, so four for a tile reduced to a point
+    pub fn perimeter(&self) -> f64 {
+        2.0 * (self.width() as f64 + self.height() as f64)
+    }
+
+    /// Returns the tile translated by a vector, or `None` if a coordinate overflows
+    pub fn checked_add(&self, vector : Point<Coord>) -> Option<Tile<Coord>> {
+        match (self.bottom.checked_add(vector), self.top.checked_add(vector)) {
+            (Some(bottom), Some(top)) => Some(Tile { bottom : bottom, top : top }),
+            _ => None,
+        }
+    }
+
+    /// Returns the tile translated by the opposite of a vector, or `None` if a coordinate
+    /// overflows
+    pub fn checked_sub(&self, vector : Point<Coord>) -> Option<Tile<Coord>> {
+        match (self.bottom.checked_sub(vector), self.top.checked_sub(vector)) {
+            (Some(bottom), Some(top)) => Some(Tile { bottom : bottom, top : top }),
+            _ => None,
+        }
+    }
+
+    /// Returns the tile translated by a vector, the corners being clamped to the bounds of the
+    /// coordinate type. The tile shrinks if it is moved past the bounds.
+    pub fn saturating_add(&self, vector : Point<Coord>) -> Tile<Coord> {
+        Tile {
+            bottom : self.bottom.saturating_add(vector),
+            top : self.top.saturating_add(vector),
+        }
+    }
+
+    /// Returns the tile translated by the opposite of a vector, the corners being clamped to the
+    /// bounds of the coordinate type. The tile shrinks if it is moved past the bounds.
+    pub fn saturating_sub(&self, vector : Point<Coord>) -> Tile<Coord> {
+        Tile {
+            bottom : self.bottom.saturating_sub(vector),
+            top : self.top.saturating_sub(vector),
+        }
+    }
+
+    /// Returns the point of the tile closest to `p`, which is `p` if the tile contains it
+    pub fn closest_point(&self, p : Point<Coord>) -> Point<Coord> {
+        Point {
+            x : min(max(p.x, self.bottom.x), self.top.x),
+            y : min(max(p.y, self.bottom.y), self.top.y),
+        }
+    }
+
+    /// Returns the smallest manhattan distance between `p` and a point of the tile, see
+    /// `Point::manhattan_distance`
+    pub fn manhattan_distance(&self, p : Point<Coord>) -> u128 {
+        self.closest_point(p).manhattan_distance(p)
+    }
+
+    /// Returns the smallest chebyshev distance between `p` and a point of the tile, see
+    /// `Point::chebyshev_distance`
+    pub fn chebyshev_distance(&self, p : Point<Coord>) -> u128 {
+        self.closest_point(p).chebyshev_distance(p)
+    }
+
+    /// Returns the smallest squared euclidean distance between `p` and a point of the tile, see
+    /// `Point::squared_distance` for its range
+    pub fn squared_distance(&self, p : Point<Coord>) -> u128 {
+        self.closest_point(p).squared_distance(p)
+    }
+}
+
+/// Errors raised when building shapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryError {
+    /// The bottom left corner of a tile is upper or right to its top right corner
+    InvertedCorners,
+}
+
+impl Error for GeometryError {
+
+    fn description(&self) -> &str {
+        match *self {
+            GeometryError::InvertedCorners => "tile corners are inverted",
+        }
+    }
+}
+
+impl Display for GeometryError {
+    fn fmt(&self, fmt : &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.description())
+    }
 }
 
 /// Returns the smallest tile containing all the tiles from the iterator.
@@ -471,6 +780,7 @@ impl<Coord : Coordinate> PartialEq<Tile<Coord>> for Point<Coord> {
 /// A tile is greater than a point if it contains it. 
 /// A tile is smaller than a point if it doesn't contain it.
 /// A tile is equal to a point if it's centered to this point and has a null width and height
+/// `Contains` is clearer for new code.
 impl<Coord : Coordinate> PartialOrd<Point<Coord>> for Tile<Coord> 
 {
 
@@ -503,6 +813,7 @@ impl<Coord : Coordinate> PartialOrd<Tile<Coord>> for Point<Coord> {
 /// A tile is greater than another if it contains it
 /// A tile is smaller than another if it is contained by it
 /// If tiles overlap, result is undefined
+/// `Contains` is clearer for new code.
 impl<Coord : Coordinate> PartialOrd for Tile<Coord> {
 
     fn partial_cmp(&self, rhs : &Tile<Coord>) -> Option<Ordering> {
@@ -525,6 +836,151 @@ impl<Coord : Coordinate> PartialEq for Tile<Coord> {
     }
 }
 
+// Spatial predicates. Unlike the comparison traits above, each predicate has a single meaning for
+// every pair of shapes.
+
+/// A shape containing other shapes
+pub trait Contains<Rhs : ?Sized> {
+    /// Returns true if every point of `rhs` is a point of `self`
+    fn contains(&self, rhs : &Rhs) -> bool;
+}
+
+/// A shape sharing points with other shapes
+pub trait Intersects<Rhs : ?Sized> {
+    /// Returns true if `self` and `rhs` have at least one point in common
+    fn intersects(&self, rhs : &Rhs) -> bool;
+}
+
+/// A shape sharing no point with other shapes, implemented for every shape defining `Intersects`
+pub trait Disjoint<Rhs : ?Sized> {
+    /// Returns true if `self` and `rhs` have no point in common
+    fn is_disjoint(&self, rhs : &Rhs) -> bool;
+}
+
+impl<T : ?Sized + Intersects<Rhs>, Rhs : ?Sized> Disjoint<Rhs> for T {
+
+    fn is_disjoint(&self, rhs : &Rhs) -> bool {
+        !self.intersects(rhs)
+    }
+}
+
+/// The side of a line a shape is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// Strictly left of a vertical line or strictly below an horizontal line
+    Before,
+    /// Crossed by the line
+    On,
+    /// Strictly right of a vertical line or strictly above an horizontal line
+    After,
+}
+
+impl Side {
+
+    /// Returns the side of an interval relative to a position on the same axis
+    fn of_interval<Coord : Coordinate>(low : Coord, high : Coord, position : Coord) -> Side {
+        if high < position {
+            Side::Before
+        } else if low > position {
+            Side::After
+        } else {
+            Side::On
+        }
+    }
+}
+
+/// A shape positioned relatively to lines
+pub trait SideOf<L : ?Sized> {
+    /// Returns the side of `line` the shape is on
+    fn side_of(&self, line : &L) -> Side;
+}
+
+/// A tile contains the points between its corners, both included
+impl<Coord : Coordinate> Contains<Point<Coord>> for Tile<Coord> {
+
+    fn contains(&self, rhs : &Point<Coord>) -> bool {
+        self.bottom.x <= rhs.x && rhs.x <= self.top.x && self.bottom.y <= rhs.y && rhs.y <= self.top.y
+    }
+}
+
+impl<Coord : Coordinate> Contains<Tile<Coord>> for Tile<Coord> {
+
+    fn contains(&self, rhs : &Tile<Coord>) -> bool {
+        self.contains(&rhs.bottom) && self.contains(&rhs.top)
+    }
+}
+
+impl<Coord : Coordinate> Intersects<Point<Coord>> for Point<Coord> {
+
+    fn intersects(&self, rhs : &Point<Coord>) -> bool {
+        self == rhs
+    }
+}
+
+impl<Coord : Coordinate> Intersects<Point<Coord>> for Tile<Coord> {
+
+    fn intersects(&self, rhs : &Point<Coord>) -> bool {
+        self.contains(rhs)
+    }
+}
+
+impl<Coord : Coordinate> Intersects<Tile<Coord>> for Point<Coord> {
+
+    fn intersects(&self, rhs : &Tile<Coord>) -> bool {
+        rhs.contains(self)
+    }
+}
+
+impl<Coord : Coordinate> Intersects<Tile<Coord>> for Tile<Coord> {
+
+    fn intersects(&self, rhs : &Tile<Coord>) -> bool {
+        self.bottom.x <= rhs.top.x && rhs.bottom.x <= self.top.x &&
+            self.bottom.y <= rhs.top.y && rhs.bottom.y <= self.top.y
+    }
+}
+
+impl<Coord : Coordinate> SideOf<VerticalLine<Coord>> for Point<Coord> {
+
+    fn side_of(&self, line : &VerticalLine<Coord>) -> Side {
+        Side::of_interval(self.x, self.x, line.x)
+    }
+}
+
+impl<Coord : Coordinate> SideOf<HorizontalLine<Coord>> for Point<Coord> {
+
+    fn side_of(&self, line : &HorizontalLine<Coord>) -> Side {
+        Side::of_interval(self.y, self.y, line.y)
+    }
+}
+
+impl<Coord : Coordinate> SideOf<VerticalLine<Coord>> for Tile<Coord> {
+
+    fn side_of(&self, line : &VerticalLine<Coord>) -> Side {
+        Side::of_interval(self.bottom.x, self.top.x, line.x)
+    }
+}
+
+impl<Coord : Coordinate> SideOf<HorizontalLine<Coord>> for Tile<Coord> {
+
+    fn side_of(&self, line : &HorizontalLine<Coord>) -> Side {
+        Side::of_interval(self.bottom.y, self.top.y, line.y)
+    }
+}
+
+impl<'a, Coord : Coordinate> SideOf<Line<Coord> + 'a> for Point<Coord> {
+
+    fn side_of(&self, line : &(Line<Coord> + 'a)) -> Side {
+        line.point_side(self)
+    }
+}
+
+impl<'a, Coord : Coordinate> SideOf<Line<Coord> + 'a> for Tile<Coord> {
+
+    fn side_of(&self, line : &(Line<Coord> + 'a)) -> Side {
+        line.tile_side(self)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::cmp::Ordering;
@@ -625,6 +1081,215 @@ mod test {
         let line : Box<Line<u16>> = Box::new(HorizontalLine::new(4) );
         assert!(&*line > &Point::new(0, 0));
     }
+
+    #[test]
+    fn line_clip_tile() {
+        let tile = Tile::new(Point::new(2u16, 3), Point::new(6, 8));
+        let (left, right) = VerticalLine::new(4).clip_tile(&tile);
+        assert_eq!(left, Some(Tile::new(Point::new(2, 3), Point::new(4, 8))));
+        assert_eq!(right, Some(Tile::new(Point::new(5, 3), Point::new(6, 8))));
+
+        let (below, above) = HorizontalLine::new(3).clip_tile(&tile);
+        assert_eq!(below, Some(Tile::new(Point::new(2, 3), Point::new(6, 3))));
+        assert_eq!(above, Some(Tile::new(Point::new(2, 4), Point::new(6, 8))));
+
+        assert_eq!(VerticalLine::new(6).clip_tile(&tile), (Some(tile), None));
+        assert_eq!(HorizontalLine::new(2).clip_tile(&tile), (None, Some(tile)));
+    }
+
+    #[test]
+    fn tile_constructors() {
+        let (p1, p2) = (Point::new(4, 1), Point::new(2, 3));
+        assert_eq!(Tile::try_new(p2, p1), Err(GeometryError::InvertedCorners));
+        assert_eq!(Tile::try_new(p1, p2), Err(GeometryError::InvertedCorners));
+        assert_eq!(Tile::try_new(Point::new(2, 1), p1), Ok(Tile::new(Point::new(2, 1), p1)));
+        assert_eq!(Tile::try_new(Point::new(0, 0), Point::new(-1, -1)), Err(GeometryError::InvertedCorners));
+
+        let tile = Tile::new(Point::new(2, 1), Point::new(4, 3));
+        assert_eq!(Tile::from_corners(p1, p2), tile);
+        assert_eq!(Tile::from_corners(p2, p1), tile);
+        assert_eq!(Tile::from_corners(Point::new(2, 1), Point::new(4, 3)), tile);
+        assert_eq!(Tile::from_corners(p1, p1), Tile::from_point(p1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn tile_inverted_corners() {
+        Tile::new(Point::new(4, 1), Point::new(2, 3));
+    }
+
+    #[test]
+    fn tile_intersection() {
+        let t1 = Tile::new(Point::new(0, 0), Point::new(4, 4));
+        assert_eq!(t1.intersection(&Tile::new(Point::new(2, 3), Point::new(6, 6))), Some(Tile::new(Point::new(2, 3), Point::new(4, 4))));
+        assert_eq!(t1.intersection(&Tile::new(Point::new(4, 0), Point::new(9, 0))), Some(Tile::new(Point::new(4, 0), Point::new(4, 0))));
+        assert_eq!(t1.intersection(&Tile::new(Point::new(5, 5), Point::new(6, 6))), None);
+    }
+
+    #[test]
+    fn tile_measures() {
+        let tile = Tile::new(Point::new(1, 2), Point::new(4, 4));
+        assert_eq!((tile.width(), tile.height()), (4, 3));
+        assert_eq!(tile.area(), 12.0);
+        assert_eq!(tile.perimeter(), 14.0);
+        let point = Tile::from_point(Point::new(3u8, 3));
+        assert_eq!((point.width(), point.height(), point.area(), point.perimeter()), (1, 1, 1.0, 4.0));
+        let wide = Tile::new(Point::new(i64::min_value(), 0), Point::new(i64::max_value(), 0));
+        assert_eq!(wide.width(), 1 << 64);
+
+        let grown = tile.expand_by(2);
+        assert_eq!(grown, Tile::new(Point::new(-1, 0), Point::new(6, 6)));
+        let bounded = Tile::new(Point::new(1u8, 2), Point::new(254, 200)).expand_by(3);
+        assert_eq!(bounded, Tile::new(Point::new(0, 0), Point::new(255, 203)));
+        assert!(grown.contains_tile(&tile));
+        assert!(!tile.contains_tile(&grown));
+    }
+
+    #[test]
+    fn tile_difference() {
+        for t1 in tiles() {
+            for t2 in tiles() {
+                let parts = t1.difference(&t2);
+                assert!(parts.len() <= 4);
+                // The parts cover the points of t1 outside of t2, once
+                for p in points() {
+                    let covering = parts.iter().filter(|t| t.contains(&p)).count();
+                    assert_eq!(covering, if t1.contains(&p) && !t2.contains(&p) { 1 } else { 0 });
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn point_distances() {
+        let (p1, p2) = (Point::new(1, 5), Point::new(4, 1));
+        assert_eq!((p1.manhattan_distance(p2), p2.manhattan_distance(p1)), (7, 7));
+        assert_eq!((p1.chebyshev_distance(p2), p2.chebyshev_distance(p1)), (4, 4));
+        assert_eq!((p1.squared_distance(p2), p2.squared_distance(p1)), (25, 25));
+        assert_eq!(p1.squared_distance(p1), 0);
+
+        // Unsigned coordinates do not underflow
+        let (p1, p2) = (Point::new(0u8, 7), Point::new(3u8, 2));
+        assert_eq!((p1.manhattan_distance(p2), p2.chebyshev_distance(p1)), (8, 5));
+
+        // Distances do not overflow the coordinate type
+        let (p1, p2) = (Point::new(0u8, 0), Point::new(255u8, 255));
+        assert_eq!((p1.manhattan_distance(p2), p1.squared_distance(p2)), (510, 130050));
+        let (p1, p2) = (Point::new(i32::min_value(), 0), Point::new(i32::max_value(), 0));
+        assert_eq!(p1.chebyshev_distance(p2), (1 << 32) - 1);
+        assert_eq!(p1.squared_distance(p2), ((1 << 32) - 1) * ((1 << 32) - 1));
+        let (p1, p2) = (Point::new(i64::min_value(), i64::min_value()), Point::new(i64::max_value(), i64::max_value()));
+        assert_eq!(p1.manhattan_distance(p2), 2 * ((1 << 64) - 1));
+        assert_eq!(p1.squared_distance(p2), u128::max_value());
+    }
+
+    #[test]
+    fn tile_distances() {
+        for tile in tiles() {
+            for p in points() {
+                // The distance to a tile is the distance to its closest point
+                let closest = |d : &Fn(Point<i32>) -> u128| points().into_iter().filter(|q| tile.contains(q)).map(|q| d(q)).min().unwrap();
+                assert_eq!(tile.manhattan_distance(p), closest(&|q| q.manhattan_distance(p)));
+                assert_eq!(tile.chebyshev_distance(p), closest(&|q| q.chebyshev_distance(p)));
+                assert_eq!(tile.squared_distance(p), closest(&|q| q.squared_distance(p)));
+                assert_eq!(tile.squared_distance(p) == 0, tile.contains(&p));
+            }
+        }
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        let p = Point::new(250u8, 3);
+        assert_eq!(p.checked_add(Point::new(5, 0)), Some(Point::new(255, 3)));
+        assert_eq!(p.checked_add(Point::new(6, 0)), None);
+        assert_eq!(p.checked_sub(Point::new(0, 4)), None);
+        assert_eq!(p.saturating_add(Point::new(10, 1)), Point::new(255, 4));
+        assert_eq!(p.saturating_sub(Point::new(1, 10)), Point::new(249, 0));
+
+        let p = Point::new(i32::max_value(), i32::min_value());
+        assert_eq!(p.checked_add(Point::new(0, -1)), None);
+        assert_eq!(p.checked_sub(Point::new(-1, 0)), None);
+        assert_eq!(p.checked_sub(Point::new(1, -1)), Some(Point::new(i32::max_value() - 1, i32::min_value() + 1)));
+
+        let tile = Tile::new(Point::new(1u8, 1), Point::new(3, 250));
+        assert_eq!(tile.checked_add(Point::new(2, 5)), Some(Tile::new(Point::new(3, 6), Point::new(5, 255))));
+        assert_eq!(tile.checked_add(Point::new(0, 6)), None);
+        assert_eq!(tile.checked_sub(Point::new(2, 0)), None);
+        assert_eq!(tile.saturating_sub(Point::new(2, 0)), Tile::new(Point::new(0, 1), Point::new(1, 250)));
+        assert_eq!(tile.saturating_add(Point::new(0, 10)), Tile::new(Point::new(1, 11), Point::new(3, 255)));
+    }
+
+    /// Returns every point with coordinates between -1 and 4
+    fn points() -> Vec<Point<i32>> {
+        (-1..5).flat_map(|x| (-1..5).map(move |y| Point::new(x, y))).collect()
+    }
+
+    /// Returns every tile with corners between 0 and 3
+    fn tiles() -> Vec<Tile<i32>> {
+        let corners = (0..4).flat_map(|x| (0..4).map(move |y| Point::new(x, y))).collect::<Vec<_>>();
+        corners.iter()
+            .flat_map(|&b| corners.iter().filter(move |&&t| b <= t).map(move |&t| Tile::new(b, t)))
+            .collect()
+    }
+
+    /// Converts the old ordering between a line and a shape to the side of the line the shape is on
+    fn side(ordering : Option<Ordering>) -> Side {
+        match ordering {
+            Some(Ordering::Less) => Side::After,
+            Some(Ordering::Greater) => Side::Before,
+            _ => Side::On,
+        }
+    }
+
+    #[test]
+    fn contains_agrees_with_ordering() {
+        for tile in tiles() {
+            for p in points() {
+                assert_eq!(tile.contains(&p), p <= tile);
+                assert_eq!(tile.contains(&p), tile >= p);
+                assert_eq!(tile.intersects(&p), tile.contains(&p));
+                assert_eq!(p.intersects(&tile), tile.contains(&p));
+                assert_eq!(tile.is_disjoint(&p), !tile.contains(&p));
+            }
+            for other in tiles() {
+                assert_eq!(tile.contains(&other), other <= tile);
+            }
+        }
+    }
+
+    #[test]
+    fn intersects_shares_a_point() {
+        for t1 in tiles() {
+            for t2 in tiles() {
+                let shared = points().iter().any(|p| t1.contains(p) && t2.contains(p));
+                assert_eq!(t1.intersects(&t2), shared);
+                assert_eq!(t2.intersects(&t1), shared);
+                assert_eq!(t1.is_disjoint(&t2), !shared);
+            }
+        }
+    }
+
+    #[test]
+    fn side_of_agrees_with_ordering() {
+        for position in -1..5 {
+            let (vertical, horizontal) = (VerticalLine::new(position), HorizontalLine::new(position));
+            let lines : [Box<Line<i32>>; 2] = [Box::new(vertical), Box::new(horizontal)];
+            for tile in tiles() {
+                assert_eq!(tile.side_of(&vertical), side(vertical.partial_cmp(&tile)));
+                assert_eq!(tile.side_of(&horizontal), side(horizontal.partial_cmp(&tile)));
+                for line in &lines {
+                    assert_eq!(tile.side_of(&**line), side(line.partial_cmp(&tile)));
+                }
+            }
+            for p in points() {
+                assert_eq!(p.side_of(&vertical), side(vertical.partial_cmp(&p)));
+                assert_eq!(p.side_of(&horizontal), side(horizontal.partial_cmp(&p)));
+                for line in &lines {
+                    assert_eq!(p.side_of(&**line), side((**line).partial_cmp(&p)));
+                }
+            }
+        }
+    }
     
 
 }
@@ -0,0 +1,237 @@
+//! Spatial indexes mapping points of the plane to objects, and a dense grid implementation.
+use std::fmt::{
+    self,
+    Debug,
+    Display,
+};
+use std::error::Error;
+
+use ::geometry::{
+    Coordinate,
+    Point,
+    Tile,
+    Contains,
+};
+use ::rtree::RTree;
+use ::num::traits::ToPrimitive;
+
+/// A map from points of the plane to objects
+pub trait SpatialIndex<Coord : Coordinate, Data> {
+
+    /// Inserts a point and returns the old value associated to this point
+    fn insert(&mut self, point : Point<Coord>, data : Data) -> Option<Data>;
+
+    /// Removes a point and returns the value associated to this point, or `None` if the point is
+    /// not in the index
+    fn remove(&mut self, point : Point<Coord>) -> Option<Data>;
+
+    /// Returns a reference to the value associated to a point
+    fn find(&self, point : Point<Coord>) -> Option<&Data>;
+
+    /// Returns a mutable reference to the value associated to a point
+    fn find_mut(&mut self, point : Point<Coord>) -> Option<&mut Data>;
+
+    /// Returns the number of points in the index
+    fn len(&self) -> usize;
+
+    /// Returns true if the index contains no point
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<Coord : Coordinate, Data : Debug> SpatialIndex<Coord, Data> for RTree<Coord, Data> {
+
+    fn insert(&mut self, point : Point<Coord>, data : Data) -> Option<Data> {
+        RTree::insert(self, point, data)
+    }
+
+    fn remove(&mut self, point : Point<Coord>) -> Option<Data> {
+        RTree::remove(self, point)
+    }
+
+    fn find(&self, point : Point<Coord>) -> Option<&Data> {
+        RTree::find(self, point)
+    }
+
+    fn find_mut(&mut self, point : Point<Coord>) -> Option<&mut Data> {
+        RTree::find_mut(self, point)
+    }
+
+    fn len(&self) -> usize {
+        RTree::len(self)
+    }
+}
+
+/// A spatial index storing a slot per point of a fixed area.
+///
+/// Lookups are a single access to a vector, but the memory used grows with the area rather than
+/// with the number of points, so the grid suits small or densely populated areas.
+#[derive(Debug, Clone)]
+pub struct Grid<Coord : Coordinate, Data> {
+    area : Tile<Coord>,
+    width : usize,
+    cells : Vec<Option<Data>>,
+    len : usize,
+}
+
+impl<Coord : Coordinate, Data> Grid<Coord, Data> {
+
+    /// Creates an empty grid covering the given area.
+    ///
+    /// # Errors
+    /// `GridError::TooLarge` if the number of points of the area overflows a `usize`
+    pub fn new(area : Tile<Coord>) -> Result<Grid<Coord, Data>, GridError> {
+        let width = try!(area.width().to_usize().ok_or(GridError::TooLarge));
+        let height = try!(area.height().to_usize().ok_or(GridError::TooLarge));
+        let cells = try!(width.checked_mul(height).ok_or(GridError::TooLarge));
+        Ok(Grid {
+            area : area,
+            width : width,
+            cells : (0..cells).map(|_| None).collect(),
+            len : 0,
+        })
+    }
+
+    /// Returns the area covered by the grid
+    pub fn area(&self) -> &Tile<Coord> {
+        &self.area
+    }
+
+    /// Returns the index of the cell of a point, or `None` if the point is outside the area
+    fn index(&self, point : Point<Coord>) -> Option<usize> {
+        if self.area.contains(&point) {
+            // The offsets are computed in i128 as they overflow a signed `Coord` for wide areas
+            let origin = self.area.bottom_left_corner();
+            let x = (point.get_x().to_i128().unwrap() - origin.get_x().to_i128().unwrap()) as usize;
+            let y = (point.get_y().to_i128().unwrap() - origin.get_y().to_i128().unwrap()) as usize;
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+}
+
+impl<Coord : Coordinate, Data> SpatialIndex<Coord, Data> for Grid<Coord, Data> {
+
+    /// Inserts a point and returns the old value associated to this point.
+    ///
+    /// Panics if the point is outside the area of the grid.
+    fn insert(&mut self, point : Point<Coord>, data : Data) -> Option<Data> {
+        let index = self.index(point).expect("point outside of the grid");
+        let old = self.cells[index].take();
+        if old.is_none() {
+            self.len += 1;
+        }
+        self.cells[index] = Some(data);
+        old
+    }
+
+    fn remove(&mut self, point : Point<Coord>) -> Option<Data> {
+        let old = self.index(point).and_then(|index| self.cells[index].take());
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old
+    }
+
+    fn find(&self, point : Point<Coord>) -> Option<&Data> {
+        self.index(point).and_then(|index| self.cells[index].as_ref())
+    }
+
+    fn find_mut(&mut self, point : Point<Coord>) -> Option<&mut Data> {
+        match self.index(point) {
+            Some(index) => self.cells[index].as_mut(),
+            None => None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Errors raised when building a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridError {
+    /// The number of points of the area overflows a `usize`
+    TooLarge,
+}
+
+impl Error for GridError {
+
+    fn description(&self) -> &str {
+        match *self {
+            GridError::TooLarge => "grid too large",
+        }
+    }
+}
+
+impl Display for GridError {
+    fn fmt(&self, fmt : &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ::geometry::{Point, Tile};
+    use ::rtree::RTree;
+    use super::{Grid, GridError, SpatialIndex};
+
+    /// Runs the same operations on an index and checks the results
+    fn check_index<I : SpatialIndex<i32, u32>>(mut index : I) {
+        assert!(index.is_empty());
+        for i in 0..20 {
+            assert_eq!(index.insert(Point::new(i % 5, i / 5), i as u32), None);
+        }
+        assert_eq!(index.insert(Point::new(2, 3), 100), Some(17));
+        assert_eq!(index.len(), 20);
+
+        assert_eq!(index.find(Point::new(2, 3)), Some(&100));
+        assert_eq!(index.find(Point::new(4, 4)), None);
+        assert_eq!(index.find(Point::new(-1, 0)), None);
+        *index.find_mut(Point::new(1, 1)).unwrap() += 1;
+        assert_eq!(index.find(Point::new(1, 1)), Some(&7));
+
+        assert_eq!(index.remove(Point::new(1, 1)), Some(7));
+        assert_eq!(index.remove(Point::new(1, 1)), None);
+        assert_eq!(index.remove(Point::new(10, 10)), None);
+        assert_eq!(index.len(), 19);
+    }
+
+    #[test]
+    fn indexes() {
+        check_index(RTree::new());
+        check_index(Grid::new(Tile::new(Point::new(-2, 0), Point::new(5, 4))).unwrap());
+    }
+
+    #[test]
+    #[should_panic]
+    fn grid_outside_area() {
+        let mut grid = Grid::new(Tile::new(Point::new(0, 0), Point::new(5, 4))).unwrap();
+        grid.insert(Point::new(6, 0), ());
+    }
+
+    #[test]
+    fn grid_signed_span() {
+        let mut grid = Grid::<i8, u32>::new(Tile::new(Point::new(-128, -1), Point::new(127, 1))).unwrap();
+        assert_eq!(grid.insert(Point::new(127, 0), 1), None);
+        assert_eq!(grid.insert(Point::new(-128, 1), 2), None);
+        assert_eq!(grid.find(Point::new(127, 0)), Some(&1));
+        assert_eq!(grid.find(Point::new(-128, 1)), Some(&2));
+        assert_eq!(grid.find(Point::new(0, 0)), None);
+    }
+
+    #[test]
+    fn grid_wide_span() {
+        let grid = Grid::<i64, ()>::new(Tile::new(Point::new(i64::min_value(), 0), Point::new(i64::max_value(), 0)));
+        assert_eq!(grid.err(), Some(GridError::TooLarge));
+    }
+
+    #[test]
+    fn grid_too_many_cells() {
+        let grid = Grid::<u64, ()>::new(Tile::new(Point::new(0, 0), Point::new(u64::max_value() / 2, u64::max_value() / 2)));
+        assert_eq!(grid.err(), Some(GridError::TooLarge));
+    }
+}
@@ -0,0 +1,9 @@
+extern crate num;
+
+#[allow(dead_code)]
+pub mod geometry;
+#[allow(dead_code)]
+pub mod rtree;
+pub mod parser;
+pub mod index;
+pub mod game;
@@ -1,29 +1,13 @@
-extern crate num;
-// These modules could be a library
-#[allow(dead_code)]mod geometry;
-#[allow(dead_code)]mod rtree;
-mod parser;
-mod game;
+extern crate demo;
 
 use std::io::{
     self,
     BufReader,
     Write,
 };
-use std::fmt::{
-    self,
-    Display,
-};
-
-use parser::Parser;
-use game::GameMap;
 
-impl<Coord : geometry::Coordinate> Display for geometry::Point<Coord> {
-
-    fn fmt(&self, f : &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(f, "{} {}", self.get_x(), self.get_y())
-    }
-}
+use demo::parser::Parser;
+use demo::game::GameMap;
 
 fn main() {
     let mut parser = Parser::new(BufReader::new(io::stdin()));
@@ -14,7 +14,9 @@ use std::fmt::{
 
 use geometry::{
     Point,
+    GeometryError,
 };
+use index::GridError;
 
 
 /// A move instruction for the rover
@@ -46,6 +48,8 @@ impl RoverMove {
 /// An error occuring while reading the game map
 #[derive(Debug)]
 pub enum ParseError {
+    InvalidArena(GeometryError),
+    ArenaTooLarge(GridError),
     InvalidRoverPosition,
     InvalidDustPosition,
     InvalidMove,
@@ -59,6 +63,8 @@ impl Error for ParseError {
 
     fn description(&self) -> &str {
         match *self {
+            ParseError::InvalidArena(_) => "invalid arena size",
+            ParseError::ArenaTooLarge(_) => "arena too large for a grid",
             ParseError::InvalidRoverPosition => "initial rover position is outside the arena",
             ParseError::InvalidDustPosition => "dust is outside the arena",
             ParseError::InvalidMove => "invalid rover move instruction",
@@ -71,6 +77,8 @@ impl Error for ParseError {
 
     fn cause(&self) -> Option<&Error> {
         match *self {
+            ParseError::InvalidArena(ref e) => Some(e),
+            ParseError::ArenaTooLarge(ref e) => Some(e),
             ParseError::InvalidNumber(ref e) => Some(e),
             ParseError::InputError(ref e) => Some(e),
             _ => None,
@@ -3,8 +3,22 @@ use std::cmp::{
     PartialEq,
     PartialOrd,
     Ordering,
+    max,
+    min,
 };
-use std::fmt::Debug;
+use std::fmt::{
+    self,
+    Debug,
+    Display,
+};
+use std::error::Error;
+use std::collections::BinaryHeap;
+use std::iter::FromIterator;
+use std::ops::{
+    Deref,
+    DerefMut,
+};
+use std::sync::Arc;
 
 use std::clone::Clone;
 
@@ -13,331 +27,1672 @@ use ::geometry::{
     Point,
     Tile,
     Line,
-    VerticalLine,
-    HorizontalLine,
+    Contains,
+    Intersects,
+    Side,
+    SideOf,
     bounding_tile,
 };
 
-/// A subnode entry is either an internal tree node, or a leaf node with a point and associated
-/// data
-#[derive(Debug)]
-enum Node<Coord : Coordinate, Value : Debug> {
+pub use self::split::{
+    SplitStrategy,
+    GreedySweep,
+    MedianSplit,
+    CostSplit,
+};
+pub use self::stats::TreeStats;
+pub use self::binary::Encode;
+pub use self::entry::{
+    Entry,
+    OccupiedEntry,
+    VacantEntry,
+};
+pub use self::summary::{
+    Summary,
+    Count,
+};
+pub use self::persistent::{
+    PersistentRTree,
+    PersistentIter,
+    PersistentQuery,
+};
+pub use self::shared::SharedRTree;
+pub use self::join::{
+    Join,
+    join,
+};
+
+mod binary;
+mod entry;
+mod join;
+mod persistent;
+mod render;
+mod shared;
+mod split;
+mod stats;
+mod summary;
+
+/// A subnode entry is either an internal tree node, or a leaf node with an object and its
+/// associated data. Objects are tiles, points being stored as tiles reduced to a point.
+///
+/// The coverages of sibling nodes never overlap, so an object crossing the border of a node is
+/// clipped and each of its parts is stored in the subtree covering it. The part covering the bottom
+/// left corner of the object is a leaf holding the data, the other parts are fragments. Leaves and
+/// fragments of different objects may overlap.
+///
+/// Internal nodes hold their children through a `Child` handle `H`, which owns them in an `RTree`
+/// and shares them between versions in a `PersistentRTree`.
+#[derive(Debug, Clone)]
+enum Node<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value> = (), H = Owned<Coord, Value, S>> {
     Leaf {
-        point : Point<Coord>,
+        /// The stored object
+        tile : Tile<Coord>,
+        /// The part of the object covered by this leaf
+        clip : Tile<Coord>,
         data : Value,
     },
+    Fragment {
+        tile : Tile<Coord>,
+        clip : Tile<Coord>,
+    },
     Node {
         coverage : Tile<Coord>,
-        vector : Vec<Node<Coord, Value>>,
+        vector : Vec<H>,
+        /// The summary of the objects whose leaf is in the subtree
+        summary : S,
     }
 }
 
-impl<Coord : Coordinate, Value : Debug, Obj : ?Sized> PartialOrd<Obj> for Node<Coord, Value>
-where Obj : PartialOrd<Point<Coord>>, Obj : PartialOrd<Tile<Coord>>, Node<Coord, Value> : PartialEq<Obj> {
+/// A handle through which an internal node holds a child. The node algorithms only modify the
+/// children they dereference mutably.
+trait Child<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> : Sized + Debug + Deref<Target = Node<Coord, Value, S, Self>> + DerefMut {
 
-    #[inline]
-    fn partial_cmp(&self, rhs : &Obj) -> Option<Ordering> {
-        match *self {
-            Node::Leaf { point : ref p, .. } => rhs.partial_cmp(&Tile::from_point(*p)),
-            Node::Node { coverage : ref tile, .. } => rhs.partial_cmp(tile),
-        }
-        .map(|o| match o { Ordering::Less => Ordering::Greater, Ordering::Greater => Ordering::Less, o => o, })
+    /// Wraps a node into a handle
+    fn new(node : Node<Coord, Value, S, Self>) -> Self;
+
+    /// Unwraps the node of a handle
+    fn into_node(self) -> Node<Coord, Value, S, Self>;
+}
+
+/// The handle of a child owned by its parent, used by `RTree`
+struct Owned<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>>(Node<Coord, Value, S, Owned<Coord, Value, S>>);
+
+impl<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> Debug for Owned<Coord, Value, S> {
+    fn fmt(&self, fmt : &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+impl<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> Deref for Owned<Coord, Value, S> {
+    type Target = Node<Coord, Value, S, Owned<Coord, Value, S>>;
+
+    fn deref(&self) -> &Node<Coord, Value, S, Owned<Coord, Value, S>> {
+        &self.0
+    }
+}
+
+impl<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> DerefMut for Owned<Coord, Value, S> {
+    fn deref_mut(&mut self) -> &mut Node<Coord, Value, S, Owned<Coord, Value, S>> {
+        &mut self.0
+    }
+}
+
+impl<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> Child<Coord, Value, S> for Owned<Coord, Value, S> {
+
+    fn new(node : Node<Coord, Value, S, Owned<Coord, Value, S>>) -> Owned<Coord, Value, S> {
+        Owned(node)
+    }
+
+    fn into_node(self) -> Node<Coord, Value, S, Owned<Coord, Value, S>> {
+        self.0
     }
 }
 
-impl<Coord : Coordinate, Value : Debug, Obj : ?Sized> PartialEq<Obj> for Node<Coord, Value>
+impl<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>, H, Obj : ?Sized> PartialEq<Obj> for Node<Coord, Value, S, H>
 where
-Obj : PartialEq<Point<Coord>>, 
+Obj : PartialEq<Point<Coord>>,
 Obj : PartialEq<Tile<Coord>>,
 {
 
-    fn eq(&self, rhs : &Obj) -> bool {
-        match *self {
-            Node::Leaf { point : ref p, .. } => rhs.eq(p),
-            Node::Node { coverage : ref tile, .. } => rhs.eq(tile),
+    fn eq(&self, rhs : &Obj) -> bool {
+        match *self {
+            Node::Leaf { ref clip, .. } | Node::Fragment { ref clip, .. } => rhs.eq(clip),
+            Node::Node { coverage : ref tile, .. } => rhs.eq(tile),
+        }
+    }
+}
+
+impl<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>, H : Child<Coord, Value, S>> Node<Coord, Value, S, H> {
+
+    /// Creates a leaf holding a point
+    fn leaf(point : Point<Coord>, data : Value) -> Node<Coord, Value, S, H> {
+        let tile = Tile::from_point(point);
+        Node::Leaf { tile : tile, clip : tile, data : data }
+    }
+
+    /// Creates the leaf of an object if `data` is given, or a fragment of it
+    fn part(tile : Tile<Coord>, clip : Tile<Coord>, data : Option<Value>) -> Node<Coord, Value, S, H> {
+        match data {
+            Some(data) => Node::Leaf { tile : tile, clip : clip, data : data },
+            None => Node::Fragment { tile : tile, clip : clip },
+        }
+    }
+
+    /// Creates an internal node, summarising its children
+    fn node(coverage : Tile<Coord>, vector : Vec<H>) -> Node<Coord, Value, S, H> {
+        let summary = Node::summarise(&vector);
+        Node::Node { coverage : coverage, vector : vector, summary : summary }
+    }
+
+    /// Returns the combined summary of a set of nodes
+    fn summarise(vector : &[H]) -> S {
+        vector.iter().fold(S::empty(), |summary, child| summary.combine(&child.summary()))
+    }
+
+    /// Returns the summary of the objects whose leaf is in the subtree
+    fn summary(&self) -> S {
+        match *self {
+            Node::Leaf { ref tile, ref data, .. } => S::of(tile, data),
+            Node::Fragment { .. } => S::empty(),
+            Node::Node { ref summary, .. } => summary.clone(),
+        }
+    }
+
+    /// Recomputes the cached summary of an internal node after its children changed
+    fn refresh_summary(&mut self) {
+        if let Node::Node { ref vector, ref mut summary, .. } = *self {
+            *summary = Node::summarise(vector);
+        }
+    }
+
+    /// Returns the summary of the objects of the subtree whose bottom left corner is in `tile`.
+    /// The cached summary of a subtree is used as is when its coverage is contained in the tile.
+    fn aggregate(&self, tile : &Tile<Coord>) -> S {
+        match *self {
+            Node::Leaf { tile : ref t, ref data, .. } =>
+                if tile.contains(&t.bottom_left_corner()) { S::of(t, data) } else { S::empty() },
+            Node::Fragment { .. } => S::empty(),
+            Node::Node { ref coverage, ref vector, ref summary } =>
+                if tile.contains(coverage) {
+                    summary.clone()
+                } else if tile.intersects(coverage) {
+                    vector.iter().fold(S::empty(), |s, child| s.combine(&child.aggregate(tile)))
+                } else {
+                    S::empty()
+                },
+        }
+    }
+
+    /// Returns the smallest covering tile for the subtree
+    fn coverage(&self) -> Tile<Coord> {
+        match *self {
+            Node::Leaf { ref clip, .. } | Node::Fragment { ref clip, .. } => *clip,
+            Node::Node { coverage : ref tile, .. } => *tile,
+        }
+    }
+
+    /// Returns the object a leaf or a fragment is part of
+    fn object(&self) -> Option<Tile<Coord>> {
+        match *self {
+            Node::Leaf { ref tile, .. } | Node::Fragment { ref tile, .. } => Some(*tile),
+            Node::Node { .. } => None,
+        }
+    }
+
+    /// Returns true if the node is an internal node
+    fn is_node(&self) -> bool {
+        matches!(*self, Node::Node { .. })
+    }
+
+    /// Returns true if a part of an object is in the subtree
+    fn contains_part(&self, tile : &Tile<Coord>) -> bool {
+        match *self {
+            Node::Leaf { .. } | Node::Fragment { .. } => self.object() == Some(*tile),
+            Node::Node { ref vector, .. } =>
+                vector.iter().any(|child| tile.intersects(&child.coverage()) && child.contains_part(tile)),
+        }
+    }
+
+    /// Moves a subtree under another kind of child handles
+    fn convert<I : Child<Coord, Value, S>>(self) -> Node<Coord, Value, S, I> {
+        match self {
+            Node::Leaf { tile, clip, data } => Node::Leaf { tile : tile, clip : clip, data : data },
+            Node::Fragment { tile, clip } => Node::Fragment { tile : tile, clip : clip },
+            Node::Node { coverage, vector, summary } => Node::Node {
+                coverage : coverage,
+                vector : vector.into_iter().map(|child| I::new(child.into_node().convert())).collect(),
+                summary : summary,
+            },
+        }
+    }
+
+    /// Recursivly splits a subtree into two. All subsubtree contained by the `left` tile go to the left subtree.
+    /// Subsubtrees overlapping the `left` tile are splitted. Other subtrees go to the right subtree.
+    /// Leaves and fragments crossing the line are clipped, the leaf keeping the left part.
+    ///
+    /// The nodes rebuilt from a subset of the children of an overflowing node may become
+    /// separable, so they are split again. Returns the nodes split from the left subtree, and the
+    /// right subtree with the nodes split from it.
+    ///
+    fn partition<'a>(&mut self, line : &'a (Line<Coord> + 'a), fill_factor : usize, split : &SplitStrategy<Coord>) -> (Vec<Node<Coord, Value, S, H>>, Vec<Node<Coord, Value, S, H>>) {
+        let (left, right) =
+            match *self {
+                Node::Leaf { ref tile, ref mut clip, .. } | Node::Fragment { ref tile, ref mut clip } =>
+                {
+                    let (left, right) = line.clip_tile(clip);
+                    if let Some(left) = left {
+                        *clip = left;
+                    }
+                    return (Vec::new(), right.map(|right| Node::Fragment { tile : *tile, clip : right }).into_iter().collect())
+                },
+                Node::Node { vector : ref mut vec, .. } =>
+                {
+                    let mut left_vec = Vec::with_capacity(fill_factor);
+                    let mut right_vec = Vec::with_capacity(fill_factor);
+
+                    let mut vector = Vec::new();
+                    mem::swap(&mut vector, vec);
+
+                    for mut node in vector {
+                        match node.coverage().side_of(line) {
+                            Side::Before => left_vec.push(node),
+                            // The current node overlaps the split tile. Recursivly split the subtree
+                            Side::On =>
+                            {
+                                let (left_nodes, right_nodes) = node.partition(line, fill_factor, split);
+                                left_vec.push(node);
+                                left_vec.extend(left_nodes.into_iter().map(H::new));
+                                right_vec.extend(right_nodes.into_iter().map(H::new));
+                            },
+                            _ => right_vec.push(node),
+                        }
+                    }
+                    let left_box = bounding_tile(left_vec.iter().map(|n| n.coverage())).unwrap();
+                    let right_box = bounding_tile(right_vec.iter().map(|n| n.coverage()));
+                    let right_node = right_box.map(|tile| Node::node(tile, right_vec));
+                    (Node::node(left_box, left_vec), right_node)
+                }
+            };
+
+        *self = left;
+
+        let mut right_nodes = Vec::new();
+        if let Some(mut right) = right {
+            let nodes = right.split_node(fill_factor, split);
+            right_nodes.push(right);
+            right_nodes.extend(nodes);
+        }
+        (self.split_node(fill_factor, split), right_nodes)
+    }
+
+    /// If the node children count is greater than the fill factor, splits it until it does not
+    /// overflow, and returns the new nodes. Nodes whose children overlap too much to be
+    /// separated are left overflowing.
+    fn split_node(&mut self, fill_factor : usize, split : &SplitStrategy<Coord>) -> Vec<Node<Coord, Value, S, H>> {
+        let line =
+            match *self {
+                Node::Node { ref mut vector, .. } =>
+                    if { vector.len() <= fill_factor } {
+                        return Vec::new()
+                    } else {
+                        // The node is full and needs to be splitted
+                        let tile_set = vector.iter().map(|node| node.coverage()).collect::<Vec<_>>();
+                        // Compute the split line
+                        match split.split(&tile_set, fill_factor) {
+                            Some(line) => line,
+                            None => return Vec::new(),
+                        }
+                    },
+                _ => return Vec::new(),
+            };
+        // Recursivly split the subtree. This call needs to be moved out because self cannot be
+        // borrowed more than once at a time (borrowed at ref mut vector)
+        let (mut nodes, right_nodes) = self.partition(&*line, fill_factor, split);
+        nodes.extend(right_nodes);
+        nodes
+    }
+
+    /// Recursivly inserts the part `clip` of the object `tile` into the subtree. `data` is the
+    /// value of the object if `clip` covers its bottom left corner, and `None` for the other parts.
+    /// If the object is already in the tree, its value is replaced and the old value is returned.
+    /// Else, `None` is returned.
+    ///
+    /// The method also returns the overflow subtrees which have to be added to the upper level
+    fn insert(&mut self, tile : Tile<Coord>, clip : Tile<Coord>, data : Option<Value>, fill_factor : usize, split : &SplitStrategy<Coord>) -> (Option<Value>, Vec<Node<Coord, Value, S, H>>) {
+        let result = self.insert_part(tile, clip, data, fill_factor, split);
+        // The value of the object may have been replaced even if no node changed
+        self.refresh_summary();
+        result
+    }
+
+    fn insert_part(&mut self, tile : Tile<Coord>, clip : Tile<Coord>, mut data : Option<Value>, fill_factor : usize, split : &SplitStrategy<Coord>) -> (Option<Value>, Vec<Node<Coord, Value, S, H>>) {
+        let corner = tile.bottom_left_corner();
+        match *self {
+            // Node is the leaf of the object, swap the value and returns the old one
+            Node::Leaf { tile : ref t, data : ref mut old, .. } if *t == tile && data.is_some() =>
+                return (data.map(|data| mem::replace(old, data)), Vec::new()),
+            Node::Leaf { .. } | Node::Fragment { .. } => return (None, vec![Node::part(tile, clip, data)]),
+            Node::Node { ref mut vector, ref mut coverage, .. } =>
+            {
+                // Children overlapping the object, before they are split
+                let children =
+                    (0..vector.len())
+                    .filter(|&i| vector[i].is_node() && clip.intersects(&vector[i].coverage()))
+                    .map(|i| (i, vector[i].coverage()))
+                    .collect::<Vec<_>>();
+                let mut overflow = Vec::new();
+                let mut owner = None;
+
+                if data.is_some() {
+                    // The leaf of the object is either a child of this node, or in the subtree
+                    // covering its corner. It is searched first so that no fragment is inserted
+                    // when the object is already in the tree.
+                    let leaf = vector.iter().position(|child| match **child {
+                        Node::Leaf { tile : ref t, .. } => *t == tile,
+                        _ => false,
+                    });
+                    if let Some(index) = leaf {
+                        if let Node::Leaf { data : ref mut old, .. } = *vector[index] {
+                            return (data.map(|data| mem::replace(old, data)), Vec::new())
+                        }
+                    }
+                    owner = children.iter().find(|&&(_, ref child)| child.contains(&corner)).map(|&(index, _)| index);
+                    if let Some(index) = owner {
+                        let part = clip.intersection(&children.iter().find(|c| c.0 == index).unwrap().1).unwrap();
+                        let (old_value, nodes) = vector[index].insert(tile, part, data.take(), fill_factor, split);
+                        if old_value.is_some() {
+                            return (old_value, nodes)
+                        }
+                        overflow.extend(nodes);
+                    }
+                }
+
+                // Other parts go to the overlapping children. The remaining parts grow a child
+                // when possible, so that the objects stay at the lowest level, and are inserted at
+                // the current level otherwise.
+                let mut parts = vec![clip];
+                for &(index, ref child) in &children {
+                    if owner != Some(index) {
+                        let part = clip.intersection(child).unwrap();
+                        let (_, nodes) = vector[index].insert(tile, part, None, fill_factor, split);
+                        overflow.extend(nodes);
+                    }
+                    parts = parts.iter().flat_map(|part| part.difference(child)).collect();
+                }
+                for (i, &part) in parts.iter().enumerate() {
+                    let data = if part.contains(&corner) { data.take() } else { None };
+                    let others = parts[i + 1..].iter().cloned().chain(overflow.iter().map(|node| node.coverage())).collect::<Vec<_>>();
+                    match Node::growing_child(vector, &part, &others) {
+                        Some(index) => {
+                            *coverage = coverage.union(part);
+                            let (_, nodes) = vector[index].insert(tile, part, data, fill_factor, split);
+                            overflow.extend(nodes);
+                        },
+                        None => overflow.push(Node::part(tile, part, data)),
+                    }
+                }
+
+                if overflow.is_empty() {
+                    return (None, overflow)
+                }
+                for node in overflow {
+                    *coverage = coverage.union(node.coverage());
+                    vector.push(H::new(node));
+                }
+            }
+        };
+        (None, self.split_node(fill_factor, split))
+    }
+
+    /// Returns the index of the child node which grows the least when covering a part outside of
+    /// the coverages of the children, among the children which would not overlap their siblings
+    /// nor the `others` tiles. Returns `None` if no child can grow, the part being then stored at
+    /// the level of the children.
+    fn growing_child(vector : &[H], part : &Tile<Coord>, others : &[Tile<Coord>]) -> Option<usize> {
+        let growth = |index : usize, grown : &Tile<Coord>| (grown.area() - vector[index].coverage().area(), grown.area());
+        (0..vector.len())
+        .filter(|&index| vector[index].is_node())
+        .map(|index| (index, vector[index].coverage().union(*part)))
+        .filter(|&(index, ref grown)| {
+            others.iter().all(|other| !grown.intersects(other)) &&
+                vector.iter().enumerate().all(|(i, sibling)| i == index || !grown.intersects(&sibling.coverage()))
+        })
+        .min_by(|&(i, ref t1), &(j, ref t2)| growth(i, t1).partial_cmp(&growth(j, t2)).unwrap_or(Ordering::Equal))
+        .map(|(index, _)| index)
+    }
+
+    /// Inserts an object into the tree whose root is `root`, see `RTree::insert_tile`. Returns the
+    /// new root, and the old value of the object.
+    fn insert_root(root : Option<Node<Coord, Value, S, H>>, tile : Tile<Coord>, data : Value, fill_factor : usize, split : &SplitStrategy<Coord>) -> (Node<Coord, Value, S, H>, Option<Value>) {
+        match root {
+            Some(mut root) => {
+                let (ret_val, overflow) = root.insert(tile, tile, Some(data), fill_factor, split);
+                (Node::grow(root, overflow, fill_factor, split), ret_val)
+            },
+            None => (Node::Leaf { tile : tile, clip : tile, data : data }, None),
+        }
+    }
+
+    /// Returns the root of the tree, adding levels above `root` while the insertion into it
+    /// overflows
+    fn grow(mut root : Node<Coord, Value, S, H>, mut overflow : Vec<Node<Coord, Value, S, H>>, fill_factor : usize, split : &SplitStrategy<Coord>) -> Node<Coord, Value, S, H> {
+        while !overflow.is_empty() {
+            // Replace the root
+            let mut vector = Vec::with_capacity(fill_factor);
+            vector.extend(overflow.into_iter().map(H::new));
+            vector.push(H::new(root));
+            let tile = bounding_tile(vector.iter().map(|n| n.coverage())).unwrap();
+
+            root = Node::node(tile, vector);
+            overflow = root.split_node(fill_factor, split);
+        }
+        root
+    }
+
+    /// Removes an object and all its parts from the tree whose root is `root`, see
+    /// `RTree::remove_tile`. Returns the new root, or `None` if the tree became empty, and the
+    /// value of the object.
+    fn remove_root(root : Node<Coord, Value, S, H>, tile : &Tile<Coord>, fill_factor : usize, split : &SplitStrategy<Coord>) -> (Option<Node<Coord, Value, S, H>>, Option<Value>) {
+        match root {
+            Node::Leaf { tile : t, data, .. } if t == *tile => (None, Some(data)),
+            root @ Node::Leaf { .. } | root @ Node::Fragment { .. } => (Some(root), None),
+            mut root => {
+                let ret_val = root.remove(tile, fill_factor, split).and_then(|value| value);
+                let overflow = root.split_node(fill_factor, split);
+                if !overflow.is_empty() {
+                    return (Some(Node::grow(root, overflow, fill_factor, split)), ret_val)
+                }
+                // Shrink the root if it became empty or has a single child
+                let root =
+                    match root {
+                        Node::Node { mut vector, .. } if vector.len() <= 1 => vector.pop().map(H::into_node),
+                        root => Some(root),
+                    };
+                (root, ret_val)
+            }
+        }
+    }
+
+    /// Inserts a node at the end of a path of child indices, as if it had been inserted by
+    /// `insert`, and returns the overflow subtrees which have to be added to the upper level. The
+    /// path must lead to an internal node which has no child containing the inserted node, the
+    /// coverages along the path growing to cover it.
+    fn insert_at(&mut self, path : &[usize], node : Node<Coord, Value, S, H>, fill_factor : usize, split : &SplitStrategy<Coord>) -> Vec<Node<Coord, Value, S, H>> {
+        match *self {
+            Node::Leaf { .. } | Node::Fragment { .. } => return vec![node],
+            Node::Node { ref mut vector, ref mut coverage, .. } => {
+                *coverage = coverage.union(node.coverage());
+                let overflow =
+                    match path.split_first() {
+                        Some((&index, rest)) => vector[index].insert_at(rest, node, fill_factor, split),
+                        None => vec![node],
+                    };
+                for node in overflow {
+                    *coverage = coverage.union(node.coverage());
+                    vector.push(H::new(node));
+                }
+            }
+        }
+        self.refresh_summary();
+        self.split_node(fill_factor, split)
+    }
+
+    /// Pushes into `path` the indices of the children containing the corner of an object, down to
+    /// the leaf of this object or to the deepest node containing the corner. Returns true if the
+    /// leaf is found.
+    fn locate(&self, tile : &Tile<Coord>, path : &mut Vec<usize>) -> bool {
+        match *self {
+            Node::Leaf { tile : ref t, .. } => t == tile,
+            Node::Fragment { .. } => false,
+            Node::Node { ref vector, .. } => {
+                let corner = tile.bottom_left_corner();
+                for (index, child) in vector.iter().enumerate().filter(|&(_, child)| child.coverage().contains(&corner)) {
+                    path.push(index);
+                    // A node containing the corner is the only child containing it
+                    if child.is_node() {
+                        return child.locate(tile, path)
+                    } else if child.locate(tile, path) {
+                        return true
+                    }
+                    path.pop();
+                }
+                false
+            },
+        }
+    }
+
+    /// Returns the subnode at the end of a path of child indices
+    fn descendant(&self, path : &[usize]) -> &Node<Coord, Value, S, H> {
+        path.iter().fold(self, |node, &index| match *node {
+            Node::Node { ref vector, .. } => &*vector[index],
+            _ => panic!("path {:?} goes through a leaf", path),
+        })
+    }
+
+    /// Returns the subnode at the end of a path of child indices
+    fn descendant_mut(&mut self, path : &[usize]) -> &mut Node<Coord, Value, S, H> {
+        path.iter().fold(self, |node, &index| match *node {
+            Node::Node { ref mut vector, .. } => &mut *vector[index],
+            _ => panic!("path {:?} goes through a leaf", path),
+        })
+    }
+
+    /// Recursivly search for the leaf of an object, returns `None` if the object is not found or a
+    /// reference to the value associated with the object. The leaf covers the bottom left corner
+    /// of the object.
+    fn find(&self, tile : &Tile<Coord>) -> Option<&Value> {
+        match *self {
+            Node::Leaf { tile : ref t, ref data, .. } if t == tile => Some(data),
+            Node::Leaf { .. } | Node::Fragment { .. } => None,
+            Node::Node { ref vector, .. } => {
+                let corner = tile.bottom_left_corner();
+                vector
+                .iter()
+                .filter(|entry| entry.coverage().contains(&corner))
+                .filter_map(|node| node.find(tile))
+                .next()
+            },
+        }
+    }
+
+    /// Recursivly search for the leaf of an object, returns `None` if the object is not found or a
+    /// mutable reference to the value associated with the object
+    fn find_mut(&mut self, tile : &Tile<Coord>) -> Option<&mut Value> {
+        match *self {
+            Node::Leaf { tile : ref t, ref mut data, .. } if t == tile => Some(data),
+            Node::Leaf { .. } | Node::Fragment { .. } => None,
+            Node::Node { ref mut vector, .. } => {
+                let corner = tile.bottom_left_corner();
+                vector
+                .iter_mut()
+                .filter(|entry| entry.coverage().contains(&corner))
+                .filter_map(|node| node.find_mut(tile))
+                .next()
+            },
+        }
+    }
+
+    /// Recursivly removes the leaf and the fragments of an object from the subtree. Returns `None`
+    /// if no part of the object is in the subtree, or the value of the object if its leaf was in
+    /// the subtree.
+    ///
+    /// Leaf nodes cannot remove themselves, the removal of a leaf is handled by its parent.
+    /// Underflowing children are either dropped when empty, hoisted into the current node when
+    /// they have a single child, or merged with a sibling. The coverage is then shrunk to the
+    /// bounding tile of the remaining children.
+    fn remove(&mut self, tile : &Tile<Coord>, fill_factor : usize, split : &SplitStrategy<Coord>) -> Option<Option<Value>> {
+        match *self {
+            Node::Leaf { .. } | Node::Fragment { .. } => None,
+            Node::Node { ref mut coverage, ref mut vector, ref mut summary } =>
+            {
+                let mut found = false;
+                let mut removed = None;
+                let mut changed = Vec::new();
+                let mut overflow = Vec::new();
+
+                let mut index = 0;
+                while index < vector.len() {
+                    if vector[index].object() == Some(*tile) {
+                        // The part is a direct child of this node
+                        found = true;
+                        if let Node::Leaf { data, .. } = vector.remove(index).into_node() {
+                            removed = Some(data);
+                        }
+                    } else if !vector[index].is_node() || !tile.intersects(&vector[index].coverage()) {
+                        index += 1;
+                    } else {
+                        if let Some(value) = vector[index].remove(tile, fill_factor, split) {
+                            found = true;
+                            removed = removed.or(value);
+                            changed.push(index);
+                            // Removing an object may allow to separate the children of a node
+                            // which overflowed because they overlapped
+                            overflow.extend(vector[index].split_node(fill_factor, split));
+                        }
+                        index += 1;
+                    }
+                }
+
+                if !found {
+                    return None
+                }
+                // Condensing a child only moves the children after it
+                for &index in changed.iter().rev() {
+                    Node::condense_child(vector, index, fill_factor);
+                }
+                vector.extend(overflow.into_iter().map(H::new));
+                if let Some(tile) = bounding_tile(vector.iter().map(|n| n.coverage())) {
+                    *coverage = tile;
+                }
+                *summary = Node::summarise(vector);
+                Some(removed)
+            }
+        }
+    }
+
+    /// Fixes the child at `index` after a point was removed from its subtree.
+    ///
+    /// An empty child is dropped and a child with a single entry is replaced by this entry. A
+    /// child with less than half `fill_factor` entries is merged with a sibling if their union
+    /// does not overlap any other sibling and does not overflow.
+    fn condense_child(vector : &mut Vec<H>, index : usize, fill_factor : usize) {
+        let len =
+            match *vector[index] {
+                Node::Node { vector : ref v, .. } => v.len(),
+                _ => return,
+            };
+
+        if len == 0 {
+            vector.remove(index);
+        } else if len == 1 {
+            let child =
+                match *vector[index] {
+                    Node::Node { vector : ref mut v, .. } => v.pop().unwrap(),
+                    _ => unreachable!(),
+                };
+            vector[index] = child;
+        } else if len < fill_factor / 2 {
+            let tile = vector[index].coverage();
+            let sibling =
+                (0..vector.len())
+                .filter(|&i| i != index)
+                .find(|&i|
+                      match *vector[i] {
+                          Node::Node { vector : ref v, coverage : ref c, .. } => {
+                              let union = c.union(tile);
+                              v.len() + len <= fill_factor &&
+                                  vector.iter()
+                                  .enumerate()
+                                  .filter(|&(j, _)| j != i && j != index)
+                                  .all(|(_, n)| !union.intersects(&n.coverage()))
+                          },
+                          _ => false,
+                      });
+
+            if let Some(sibling) = sibling {
+                let child = vector.remove(index).into_node();
+                let sibling = if sibling > index { sibling - 1 } else { sibling };
+                if let (Node::Node { vector : children, coverage : tile, .. }, &mut Node::Node { ref mut vector, ref mut coverage, .. }) = (child, &mut *vector[sibling]) {
+                    *coverage = coverage.union(tile);
+                    vector.extend(children);
+                }
+                vector[sibling].refresh_summary();
+            }
+        }
+    }
+
+    /// Recursivly checks the structural properties of the subtree, see `RTree::check_invariants`.
+    /// Violations are appended to `violations`, the leaves of the subtree to `leaves` and the
+    /// clipped parts of objects to `parts`.
+    fn check<'a>(&'a self, fill_factor : usize, split : &SplitStrategy<Coord>, violations : &mut Vec<Violation<Coord>>, leaves : &mut Vec<(Tile<Coord>, &'a Value)>, parts : &mut Vec<(Tile<Coord>, Tile<Coord>)>) {
+        match *self {
+            Node::Leaf { ref tile, ref clip, ref data } => {
+                leaves.push((*tile, data));
+                if !(tile.contains(clip) && clip.contains(&tile.bottom_left_corner())) {
+                    violations.push(Violation::WrongClip { tile : *tile, clip : *clip });
+                }
+                if clip != tile {
+                    parts.push((*tile, *clip));
+                }
+            },
+            Node::Fragment { ref tile, ref clip } => {
+                if !tile.contains(clip) || clip.contains(&tile.bottom_left_corner()) {
+                    violations.push(Violation::WrongClip { tile : *tile, clip : *clip });
+                }
+                parts.push((*tile, *clip));
+            },
+            Node::Node { ref coverage, ref vector, .. } =>
+            {
+                if vector.is_empty() {
+                    violations.push(Violation::EmptyNode { coverage : *coverage });
+                }
+                if vector.len() > fill_factor {
+                    // The only nodes allowed to overflow are those `split_node` leaves as they
+                    // are : the split strategy of the tree finds no line separating the children
+                    let tiles = vector.iter().map(|n| n.coverage()).collect::<Vec<_>>();
+                    if split.split(&tiles, fill_factor).is_some() {
+                        violations.push(Violation::Overflow { coverage : *coverage, children : vector.len() });
+                    }
+                }
+                for (i, child) in vector.iter().enumerate() {
+                    let tile = child.coverage();
+                    if !coverage.contains(&tile) {
+                        violations.push(Violation::OutsideParent { parent : *coverage, child : tile });
+                    }
+                    for sibling in &vector[i + 1..] {
+                        // Parts of different objects may overlap
+                        let objects = (child.object(), sibling.object());
+                        let distinct_objects = objects.0.is_some() && objects.1.is_some() && objects.0 != objects.1;
+                        if tile.intersects(&sibling.coverage()) && !distinct_objects {
+                            violations.push(Violation::Overlap { first : tile, second : sibling.coverage() });
+                        }
+                    }
+                    child.check(fill_factor, split, violations, leaves, parts);
+                }
+            }
+        }
+    }
+
+    /// Packs a set of distinct points into a subtree, using the Sort-Tile-Recursive algorithm.
+    ///
+    /// The points are sorted by `x` and cut into vertical slices, then each slice is sorted by `y`
+    /// and cut into runs. Each run is recursively packed into a child subtree. Cuts never separate
+    /// two points on the same line, so that the children coverages do not overlap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `entries` is empty
+    fn pack(mut entries : Vec<(Point<Coord>, Value)>, fill_factor : usize) -> Node<Coord, Value, S, H> {
+        if entries.len() == 1 {
+            let (point, data) = entries.pop().unwrap();
+            return Node::leaf(point, data)
+        }
+
+        let groups =
+            if entries.len() <= fill_factor {
+                (0..entries.len()).map(|i| (i, i + 1)).collect()
+            } else {
+                // Number of points in each child subtree for a full tree
+                let mut capacity = fill_factor;
+                while capacity * fill_factor < entries.len() {
+                    capacity *= fill_factor;
+                }
+                // Points on the same line may make the runs longer than `capacity` and produce more
+                // children than `fill_factor`. Grow the runs until they fit in the node.
+                let mut groups = Self::tile_groups(&mut entries, capacity);
+                while groups.len() > fill_factor {
+                    capacity *= 2;
+                    groups = Self::tile_groups(&mut entries, capacity);
+                }
+                if groups.len() == 1 {
+                    // All the runs were merged, cut the points in two halves instead. The points
+                    // are distinct so they cannot all be on the same vertical and horizontal line.
+                    entries.sort_by(|e1, e2| e1.0.vertical_cmp(e2.0).then(e1.0.horizontal_cmp(e2.0)));
+                    let cut =
+                        match middle_cut(&entries, |e1, e2| e1.0.get_x() == e2.0.get_x()) {
+                            Some(cut) => cut,
+                            None => {
+                                entries.sort_by(|e1, e2| e1.0.horizontal_cmp(e2.0).then(e1.0.vertical_cmp(e2.0)));
+                                middle_cut(&entries, |e1, e2| e1.0.get_y() == e2.0.get_y()).unwrap()
+                            },
+                        };
+                    groups = vec![(0, cut), (cut, entries.len())];
+                }
+                groups
+            };
+
+        let mut vector = Vec::with_capacity(groups.len());
+        for &(start, _) in groups.iter().rev() {
+            let run = entries.split_off(start);
+            vector.push(H::new(Node::pack(run, fill_factor)));
+        }
+        vector.reverse();
+
+        Node::node(bounding_tile(vector.iter().map(|n| n.coverage())).unwrap(), vector)
+    }
+
+    /// Sorts the entries and returns the `(start, end)` bounds of the runs of about `capacity`
+    /// points computed by the Sort-Tile-Recursive algorithm.
+    fn tile_groups(entries : &mut Vec<(Point<Coord>, Value)>, capacity : usize) -> Vec<(usize, usize)> {
+        let count = entries.len().div_ceil(capacity);
+        let slice_count = (count as f64).sqrt().ceil() as usize;
+
+        entries.sort_by(|e1, e2| e1.0.vertical_cmp(e2.0).then(e1.0.horizontal_cmp(e2.0)));
+
+        let mut groups = Vec::with_capacity(count);
+        for (start, end) in slice_cuts(entries, slice_count * capacity, |e1, e2| e1.0.get_x() == e2.0.get_x()) {
+            let slice = &mut entries[start..end];
+            slice.sort_by(|e1, e2| e1.0.horizontal_cmp(e2.0).then(e1.0.vertical_cmp(e2.0)));
+            groups.extend(
+                slice_cuts(slice, capacity, |e1, e2| e1.0.get_y() == e2.0.get_y())
+                .into_iter()
+                .map(|(s, e)| (start + s, start + e)));
+        }
+        groups
+    }
+}
+
+/// Returns the index closest to the middle of a sorted slice which does not separate two elements
+/// for which `same_line` returns true, or `None` if all the elements are on the same line.
+fn middle_cut<T, F>(slice : &[T], same_line : F) -> Option<usize>
+where F : Fn(&T, &T) -> bool
+{
+    let half = slice.len() / 2;
+    (1..slice.len())
+        .filter(|&i| !same_line(&slice[i - 1], &slice[i]))
+        .min_by_key(|&i| i.abs_diff(half))
+}
+
+/// Cuts a sorted slice into runs of at least `size` elements, except for the last one, and returns
+/// the `(start, end)` bounds of the runs. Two consecutive elements for which `same_line` returns true
+/// are never separated.
+fn slice_cuts<T, F>(slice : &[T], size : usize, same_line : F) -> Vec<(usize, usize)>
+where F : Fn(&T, &T) -> bool
+{
+    let mut cuts = Vec::new();
+    let mut start = 0;
+    while start < slice.len() {
+        let mut end = min(start + size, slice.len());
+        while end < slice.len() && same_line(&slice[end - 1], &slice[end]) {
+            end += 1;
+        }
+        cuts.push((start, end));
+        start = end;
+    }
+    cuts
+}
+
+/// An entry of the nearest neighbour priority queue. Entries are ordered by decreasing distance so
+/// that the `BinaryHeap` pops the closest entry first
+struct NearestEntry<'a, Coord : Coordinate + 'a, Value : Debug + 'a, S : Summary<Coord, Value> + 'a> {
+    distance : u128,
+    node : &'a Node<Coord, Value, S>,
+}
+
+impl<'a, Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> PartialEq for NearestEntry<'a, Coord, Value, S> {
+    fn eq(&self, rhs : &NearestEntry<'a, Coord, Value, S>) -> bool {
+        self.distance == rhs.distance
+    }
+}
+
+impl<'a, Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> Eq for NearestEntry<'a, Coord, Value, S> { }
+
+impl<'a, Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> PartialOrd for NearestEntry<'a, Coord, Value, S> {
+    fn partial_cmp(&self, rhs : &NearestEntry<'a, Coord, Value, S>) -> Option<Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl<'a, Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> Ord for NearestEntry<'a, Coord, Value, S> {
+    fn cmp(&self, rhs : &NearestEntry<'a, Coord, Value, S>) -> Ordering {
+        rhs.distance.cmp(&self.distance)
+    }
+}
+
+/// Iterates over the objects of a subtree by increasing distance to a query point. The tree is
+/// walked best-first : nodes are expanded in the order of the minimum distance between the query
+/// point and their coverage tile
+struct Nearest<'a, Coord : Coordinate + 'a, Value : Debug + 'a, S : Summary<Coord, Value> + 'a> {
+    point : Point<Coord>,
+    heap : BinaryHeap<NearestEntry<'a, Coord, Value, S>>,
+    /// The root of the tree, to find the value of an object reached through a fragment
+    root : Option<&'a Node<Coord, Value, S>>,
+}
+
+impl<'a, Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> Nearest<'a, Coord, Value, S> {
+
+    fn new(point : Point<Coord>, root : Option<&'a Node<Coord, Value, S>>) -> Nearest<'a, Coord, Value, S> {
+        let mut heap = BinaryHeap::new();
+        if let Some(node) = root {
+            heap.push(NearestEntry { distance : node.coverage().squared_distance(point), node : node });
+        }
+        Nearest {
+            point : point,
+            heap : heap,
+            root : root,
+        }
+    }
+
+    /// Returns true if the part `clip` of the object `tile` reports the object. The parts of an
+    /// object do not overlap, so only one of them contains the point of the object closest to the
+    /// query point, and this part is as close to the query point as the object.
+    fn reports(&self, tile : &Tile<Coord>, clip : &Tile<Coord>) -> bool {
+        clip.contains(&tile.closest_point(self.point))
+    }
+}
+
+impl<'a, Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> Iterator for Nearest<'a, Coord, Value, S> {
+    type Item = (Tile<Coord>, &'a Value);
+
+    fn next(&mut self) -> Option<(Tile<Coord>, &'a Value)> {
+        while let Some(NearestEntry { node, .. }) = self.heap.pop() {
+            match *node {
+                Node::Leaf { ref tile, ref clip, ref data } =>
+                    if self.reports(tile, clip) {
+                        return Some((*tile, data))
+                    },
+                Node::Fragment { ref tile, ref clip } =>
+                    if self.reports(tile, clip) {
+                        return Some((*tile, self.root.and_then(|root| root.find(tile)).unwrap()))
+                    },
+                Node::Node { ref vector, .. } =>
+                    for child in vector {
+                        let distance = child.coverage().squared_distance(self.point);
+                        self.heap.push(NearestEntry { distance : distance, node : &**child });
+                    },
+            }
+        }
+        None
+    }
+}
+
+/// A balanced tree storing points and tile shaped objects in a 2D plane. Internal nodes cache the
+/// summary `S` of their subtree, see `Summary` and `RTree::aggregate`.
+#[derive(Debug)]
+pub struct RTree<Coord : Coordinate, Data : Debug, S : Summary<Coord, Data> = ()> {
+    fill_factor : usize,
+    split : Arc<SplitStrategy<Coord>>,
+    len : usize,
+    root : Option<Node<Coord, Data, S>>,
+}
+
+/// A builder configuring the fill factor and the split strategy of an `RTree`. By default, nodes
+/// have at most 4 children and are split with the `GreedySweep` strategy.
+#[derive(Debug)]
+pub struct RTreeBuilder<Coord : Coordinate> {
+    fill_factor : usize,
+    split : Arc<SplitStrategy<Coord>>,
+}
+
+impl<Coord : Coordinate> RTreeBuilder<Coord> {
+
+    /// Creates a builder with the default configuration
+    pub fn new() -> RTreeBuilder<Coord> {
+        RTreeBuilder {
+            fill_factor : 4,
+            split : Arc::new(GreedySweep),
+        }
+    }
+
+    /// Sets the maximum number of children of a node
+    pub fn radix(mut self, radix : usize) -> RTreeBuilder<Coord> {
+        self.fill_factor = radix;
+        self
+    }
+
+    /// Sets the strategy used to split overflowing nodes
+    pub fn split_strategy<S : SplitStrategy<Coord> + 'static>(mut self, split : S) -> RTreeBuilder<Coord> {
+        self.split = Arc::new(split);
+        self
+    }
+
+    /// Creates a new empty `RTree`, caching the summaries `S` of the subtrees
+    pub fn build<Data : Debug, S : Summary<Coord, Data>>(self) -> RTree<Coord, Data, S> {
+        RTree {
+            fill_factor : self.fill_factor,
+            split : self.split,
+            len : 0,
+            root : None,
+        }
+    }
+
+    /// Creates a new `RTree` containing the given points, see `RTree::bulk_load`
+    pub fn bulk_load<Data : Debug, S : Summary<Coord, Data>>(self, mut entries : Vec<(Point<Coord>, Data)>) -> RTree<Coord, Data, S> {
+        // Stable sort, so that the last value of a point comes last
+        entries.sort_by(|e1, e2| e1.0.vertical_cmp(e2.0).then(e1.0.horizontal_cmp(e2.0)));
+        let mut unique : Vec<(Point<Coord>, Data)> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            if unique.last().map(|last| last.0 == entry.0).unwrap_or(false) {
+                *unique.last_mut().unwrap() = entry;
+            } else {
+                unique.push(entry);
+            }
+        }
+
+        RTree {
+            fill_factor : self.fill_factor,
+            split : self.split,
+            len : unique.len(),
+            root : if unique.is_empty() { None } else { Some(Node::pack(unique, self.fill_factor)) },
+        }
+    }
+}
+
+impl<Coord : Coordinate> Default for RTreeBuilder<Coord> {
+
+    fn default() -> RTreeBuilder<Coord> {
+        RTreeBuilder::new()
+    }
+}
+
+/// Trees without summaries can give mutable access to the values in place, since no cached
+/// summary depends on them
+impl<Coord : Coordinate, Data : Debug> RTree<Coord, Data> {
+
+    /// Creates a new empty `RTree`
+    pub fn new() -> RTree<Coord, Data> {
+        RTreeBuilder::new().build()
+    }
+
+    /// Creates a new empty `RTree` covering the given tile with an user defined radix
+    pub fn with_radix(radix : usize) -> RTree<Coord, Data> {
+        RTreeBuilder::new().radix(radix).build()
+    }
+
+    /// Returns a builder to configure the tree
+    pub fn builder() -> RTreeBuilder<Coord> {
+        RTreeBuilder::new()
+    }
+
+    /// Creates a new `RTree` containing the given points, packed bottom-up with the
+    /// Sort-Tile-Recursive algorithm. If a point appears more than once, the last value is kept.
+    ///
+    /// This is much faster than inserting the points one by one and produces fuller nodes and a
+    /// shallower tree.
+    pub fn bulk_load(entries : Vec<(Point<Coord>, Data)>) -> RTree<Coord, Data> {
+        RTreeBuilder::new().bulk_load(entries)
+    }
+
+    /// Creates a new `RTree` with an user defined radix containing the given points, see
+    /// `RTree::bulk_load`
+    pub fn bulk_load_with_radix(radix : usize, entries : Vec<(Point<Coord>, Data)>) -> RTree<Coord, Data> {
+        RTreeBuilder::new().radix(radix).bulk_load(entries)
+    }
+
+    /// Returns an iterator over the objects of the tree and mutable references to their
+    /// associated values. Objects are visited in depth first order of their leaves.
+    pub fn iter_mut<'a>(&'a mut self) -> IterMut<'a, Coord, Data> {
+        IterMut {
+            stack : self.root.iter_mut().collect(),
+            remaining : self.len,
+        }
+    }
+
+    /// Recursivly search for a matching point, returns `None` if no point is found or a mutable
+    /// reference to the value associated with the point
+    #[inline]
+    pub fn find_mut(&mut self, point : Point<Coord>) -> Option<&mut Data> {
+        self.find_tile_mut(&Tile::from_point(point))
+    }
+
+    /// Recursivly search for an object, returns `None` if the object is not found or a mutable
+    /// reference to its value
+    #[inline]
+    pub fn find_tile_mut(&mut self, tile : &Tile<Coord>) -> Option<&mut Data> {
+        self.root.as_mut().and_then(|r| r.find_mut(tile))
+    }
+
+    /// Returns the entry of a point, to read, update, insert or remove its value in place. The
+    /// tree is only searched once, an occupied entry or an insertion into a vacant entry then
+    /// follow the path found by the search.
+    pub fn entry<'a>(&'a mut self, point : Point<Coord>) -> Entry<'a, Coord, Data> {
+        let mut path = Vec::new();
+        let found = self.root.as_ref().map(|root| root.locate(&Tile::from_point(point), &mut path)).unwrap_or(false);
+        if found {
+            Entry::Occupied(OccupiedEntry::new(self, point, path))
+        } else {
+            Entry::Vacant(VacantEntry::new(self, point, path))
+        }
+    }
+}
+
+impl<Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> RTree<Coord, Data, S> {
+
+    /// Inserts a point into the RTree, and returns the old value associated to this point.
+    ///
+    pub fn insert(&mut self, point : Point<Coord>, data : Data) -> Option<Data> {
+        self.insert_tile(Tile::from_point(point), data)
+    }
+
+    /// Inserts a tile shaped object into the RTree, and returns the old value associated to this
+    /// tile. An object crossing the border of nodes is clipped into several parts.
+    pub fn insert_tile(&mut self, tile : Tile<Coord>, data : Data) -> Option<Data> {
+        let ret_val = self.insert_root(tile, data);
+        if ret_val.is_none() {
+            self.len += 1;
+        }
+        ret_val
+    }
+
+    fn insert_root(&mut self, tile : Tile<Coord>, data : Data) -> Option<Data> {
+        let (root, ret_val) = Node::insert_root(self.root.take(), tile, data, self.fill_factor, &*self.split);
+        self.root = Some(root);
+        ret_val
+    }
+
+    /// Sets the root of the tree, adding levels while the insertion into the old root overflows
+    fn set_root(&mut self, root : Node<Coord, Data, S>, overflow : Vec<Node<Coord, Data, S>>) {
+        self.root = Some(Node::grow(root, overflow, self.fill_factor, &*self.split));
+    }
+
+    /// Removes a point from the RTree, and returns the value associated to this point or `None`
+    /// if the point is not in the tree.
+    pub fn remove(&mut self, point : Point<Coord>) -> Option<Data> {
+        self.remove_tile(&Tile::from_point(point))
+    }
+
+    /// Removes an object and all its parts from the RTree, and returns the value associated to
+    /// this tile or `None` if the object is not in the tree.
+    pub fn remove_tile(&mut self, tile : &Tile<Coord>) -> Option<Data> {
+        let ret_val = self.remove_root(tile);
+        if ret_val.is_some() {
+            self.len -= 1;
+        }
+        ret_val
+    }
+
+    fn remove_root(&mut self, tile : &Tile<Coord>) -> Option<Data> {
+        match self.root.take() {
+            None => None,
+            Some(root) => {
+                let (root, ret_val) = Node::remove_root(root, tile, self.fill_factor, &*self.split);
+                self.root = root;
+                ret_val
+            }
+        }
+    }
+
+    /// Checks the structural properties of the R+tree :
+    ///
+    ///  - every child is contained in the coverage of its parent
+    ///  - the coverages of sibling nodes do not overlap, except for parts of different objects
+    ///  - no node is empty, and no node has more than `fill_factor` children unless the split
+    ///    strategy of the tree finds no line to separate them
+    ///  - every object is reachable by `find_tile`
+    ///  - the parts of a clipped object exactly cover it, its leaf covering its bottom left corner
+    ///  - `len` is the number of objects in the tree
+    ///
+    /// Returns an error listing all the violations found.
+    pub fn check_invariants(&self) -> Result<(), InvariantError<Coord>> {
+        let mut violations = Vec::new();
+        let mut leaves = Vec::with_capacity(self.len);
+        let mut parts = Vec::new();
+
+        if let Some(ref root) = self.root {
+            root.check(self.fill_factor, &*self.split, &mut violations, &mut leaves, &mut parts);
+        }
+
+        for &(tile, data) in &leaves {
+            if !self.find_tile(&tile).map(|found| found as *const Data == data as *const Data).unwrap_or(false) {
+                violations.push(Violation::Unreachable { tile : tile });
+            }
+        }
+
+        // Group the parts by object
+        parts.sort_by_key(|&(tile, _)| {
+            let (bottom, top) = (tile.bottom_left_corner(), tile.top_right_corner());
+            (bottom.get_x(), bottom.get_y(), top.get_x(), top.get_y())
+        });
+        // Compare exact cell counts, `None` when they overflow a `u128`
+        let cells = |tile : &Tile<Coord>| tile.width().checked_mul(tile.height());
+        let mut start = 0;
+        while start < parts.len() {
+            let tile = parts[start].0;
+            let end = start + parts[start..].iter().take_while(|&&(t, _)| t == tile).count();
+            let covered = parts[start..end].iter().try_fold(0u128, |sum, &(_, ref clip)| cells(clip).and_then(|c| sum.checked_add(c)));
+            if covered != cells(&tile) {
+                violations.push(Violation::Incomplete { tile : tile });
+            }
+            start = end;
+        }
+
+        if leaves.len() != self.len {
+            violations.push(Violation::WrongLength { len : self.len, count : leaves.len() });
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(InvariantError { violations : violations })
+        }
+    }
+
+    /// Returns statistics about the structure of the tree : depth, number of nodes, fan-out, fill
+    /// ratio per level and coverage areas.
+    pub fn stats(&self) -> TreeStats {
+        TreeStats::new(self.root.as_ref(), self.fill_factor)
+    }
+
+    /// Returns a Graphviz description of the tree, each internal node being drawn as a cluster
+    /// labelled by its coverage and containing its children.
+    pub fn to_dot(&self) -> String {
+        render::dot(self.root.as_ref())
+    }
+
+    /// Returns an SVG picture of the tree, node coverages being drawn as nested rectangles
+    /// coloured by depth and points as dots.
+    pub fn to_svg(&self) -> String {
+        render::svg(self.root.as_ref())
+    }
+
+    /// Returns the number of objects in the tree
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the tree contains no object
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the objects from the tree
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the objects of the tree and their associated values. Objects are
+    /// visited in depth first order of their leaves.
+    pub fn iter<'a>(&'a self) -> Iter<'a, Coord, Data, S> {
+        Iter {
+            leaves : Leaves::new(self.root.as_ref()),
+            remaining : self.len,
+        }
+    }
+
+    /// Recursivly search for a matching point, returns `None` if no point is found or a
+    /// reference to the value associated with the point
+    #[inline]
+    pub fn find(&self, point : Point<Coord>) -> Option<&Data> {
+        self.find_tile(&Tile::from_point(point))
+    }
+
+    /// Recursivly search for an object, returns `None` if the object is not found or a reference
+    /// to its value
+    #[inline]
+    pub fn find_tile(&self, tile : &Tile<Coord>) -> Option<&Data> {
+        self.root.as_ref().and_then(|r| r.find(tile))
+    }
+
+    /// Returns a lazy iterator over all the objects intersecting the given tile and their
+    /// associated values. Subtrees whose coverage does not overlap the tile are skipped, and each
+    /// object is returned once even if it is clipped.
+    pub fn query_tile<'a>(&'a self, tile : &Tile<Coord>) -> TileQuery<'a, Coord, Data, S> {
+        TileQuery {
+            query : Intersecting::new(*tile, self.root.as_ref()),
+        }
+    }
+
+    /// Returns a lazy iterator over all the objects containing the given point and their
+    /// associated values
+    pub fn query_point<'a>(&'a self, point : Point<Coord>) -> TileQuery<'a, Coord, Data, S> {
+        self.query_tile(&Tile::from_point(point))
+    }
+
+    /// Returns the combined summary of the objects whose bottom left corner is in the given tile.
+    /// The cached summaries of the subtrees covered by the tile are used without visiting them.
+    pub fn aggregate(&self, tile : &Tile<Coord>) -> S {
+        self.root.as_ref().map(|root| root.aggregate(tile)).unwrap_or_else(S::empty)
+    }
+
+    /// Calls `f` on the value of an object and updates the cached summaries, returning `None` if
+    /// the object is not in the tree or the result of `f`
+    pub fn update<R, F : FnOnce(&mut Data) -> R>(&mut self, tile : &Tile<Coord>, f : F) -> Option<R> {
+        let mut path = Vec::new();
+        let root =
+            match self.root {
+                Some(ref mut root) if root.locate(tile, &mut path) => root,
+                _ => return None,
+            };
+        let result =
+            match *root.descendant_mut(&path) {
+                Node::Leaf { ref mut data, .. } => f(data),
+                _ => unreachable!("the path of an object leads to its leaf"),
+            };
+        for depth in (0..path.len()).rev() {
+            root.descendant_mut(&path[..depth]).refresh_summary();
+        }
+        Some(result)
+    }
+
+    /// Returns the closest object to the given point, using the euclidean distance, and its
+    /// associated value. Returns `None` if the tree is empty.
+    pub fn nearest(&self, point : Point<Coord>) -> Option<(Tile<Coord>, &Data)> {
+        Nearest::new(point, self.root.as_ref()).next()
+    }
+
+    /// Returns the `k` closest objects to the given point and their associated values, sorted by
+    /// increasing euclidean distance. Less than `k` objects are returned if the tree is smaller.
+    pub fn k_nearest(&self, point : Point<Coord>, k : usize) -> Vec<(Tile<Coord>, &Data)> {
+        Nearest::new(point, self.root.as_ref()).take(k).collect()
+    }
+}
+
+/// A violation of the R+tree structural properties
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Violation<Coord : Coordinate> {
+    /// A child is not contained in the coverage of its parent
+    OutsideParent { parent : Tile<Coord>, child : Tile<Coord> },
+    /// The coverages of two siblings overlap
+    Overlap { first : Tile<Coord>, second : Tile<Coord> },
+    /// A node has more children than the fill factor
+    Overflow { coverage : Tile<Coord>, children : usize },
+    /// An internal node has no children
+    EmptyNode { coverage : Tile<Coord> },
+    /// `find_tile` does not return the leaf of an object
+    Unreachable { tile : Tile<Coord> },
+    /// A part is not inside its object, or the leaf of a clipped object does not cover its bottom
+    /// left corner, or a fragment does
+    WrongClip { tile : Tile<Coord>, clip : Tile<Coord> },
+    /// The parts of a clipped object do not cover it exactly
+    Incomplete { tile : Tile<Coord> },
+    /// The length of the tree is not the number of leaves
+    WrongLength { len : usize, count : usize },
+}
+
+impl<Coord : Coordinate> Display for Violation<Coord> {
+    fn fmt(&self, fmt : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Violation::OutsideParent { ref parent, ref child } =>
+                write!(fmt, "child {:?} is outside its parent coverage {:?}", child, parent),
+            Violation::Overlap { ref first, ref second } =>
+                write!(fmt, "sibling coverages {:?} and {:?} overlap", first, second),
+            Violation::Overflow { ref coverage, children } =>
+                write!(fmt, "node {:?} has {} children", coverage, children),
+            Violation::EmptyNode { ref coverage } =>
+                write!(fmt, "node {:?} has no children", coverage),
+            Violation::Unreachable { ref tile } =>
+                write!(fmt, "object {:?} is not reachable", tile),
+            Violation::WrongClip { ref tile, ref clip } =>
+                write!(fmt, "part {:?} of object {:?} is misplaced", clip, tile),
+            Violation::Incomplete { ref tile } =>
+                write!(fmt, "the parts of object {:?} do not cover it", tile),
+            Violation::WrongLength { len, count } =>
+                write!(fmt, "tree length is {} but it contains {} objects", len, count),
+        }
+    }
+}
+
+/// An error returned by `RTree::check_invariants` listing all the violations of the tree
+/// properties
+#[derive(Debug)]
+pub struct InvariantError<Coord : Coordinate> {
+    violations : Vec<Violation<Coord>>,
+}
+
+impl<Coord : Coordinate> InvariantError<Coord> {
+
+    /// Returns the violations found in the tree
+    pub fn violations(&self) -> &[Violation<Coord>] {
+        &self.violations
+    }
+}
+
+impl<Coord : Coordinate> Error for InvariantError<Coord> {
+
+    fn description(&self) -> &str {
+        "R+tree invariants violated"
+    }
+}
+
+impl<Coord : Coordinate> Display for InvariantError<Coord> {
+    fn fmt(&self, fmt : &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{} R+tree invariants violated :", self.violations.len()));
+        for violation in &self.violations {
+            try!(write!(fmt, "\n - {}", violation));
+        }
+        Ok(())
+    }
+}
+
+/// Walks the leaves of a subtree in depth first order, see `Iter`
+struct Leaves<'a, Coord : Coordinate + 'a, Value : Debug + 'a, S : Summary<Coord, Value> + 'a, H : 'a> {
+    stack : Vec<&'a Node<Coord, Value, S, H>>,
+}
+
+impl<'a, Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>, H : Child<Coord, Value, S>> Leaves<'a, Coord, Value, S, H> {
+
+    fn new(root : Option<&'a Node<Coord, Value, S, H>>) -> Leaves<'a, Coord, Value, S, H> {
+        Leaves { stack : root.into_iter().collect() }
+    }
+}
+
+impl<'a, Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>, H : Child<Coord, Value, S>> Iterator for Leaves<'a, Coord, Value, S, H> {
+    type Item = (Tile<Coord>, &'a Value);
+
+    fn next(&mut self) -> Option<(Tile<Coord>, &'a Value)> {
+        while let Some(node) = self.stack.pop() {
+            match *node {
+                Node::Leaf { ref tile, ref data, .. } => return Some((*tile, data)),
+                Node::Fragment { .. } => (),
+                // Push in reverse order so that children are visited in order
+                Node::Node { ref vector, .. } => self.stack.extend(vector.iter().rev().map(|child| &**child)),
+            }
         }
+        None
     }
 }
 
-impl<Coord : Coordinate, Value : Debug> Node<Coord, Value> {
+/// An iterator over the entries of an `RTree`, created by `RTree::iter`
+pub struct Iter<'a, Coord : Coordinate + 'a, Data : Debug + 'a, S : Summary<Coord, Data> + 'a = ()> {
+    leaves : Leaves<'a, Coord, Data, S, Owned<Coord, Data, S>>,
+    remaining : usize,
+}
 
-    /// Returns the smallest covering tile for the subtree
-    fn coverage(&self) -> Tile<Coord> {
-        match *self {
-            Node::Leaf { point : ref p, .. } => Tile::from_point(*p),
-            Node::Node { coverage : ref tile, .. } => *tile,
+impl<'a, Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> Iterator for Iter<'a, Coord, Data, S> {
+    type Item = (Tile<Coord>, &'a Data);
+
+    fn next(&mut self) -> Option<(Tile<Coord>, &'a Data)> {
+        let next = self.leaves.next();
+        if next.is_some() {
+            self.remaining -= 1;
         }
+        next
     }
 
-    /// Computes a partition of a tile vector into two subvectors. Put at most `fill_factor` elements into the first
-    /// vector and the remaining elements into the second node. Returns the splitting line
-    ///
-    /// # Panics 
-    ///
-    /// Panics when the node has less than `fill_factor` elements or if fill_factor is less than 2
-    fn sweep(tile_set : Vec<Tile<Coord>>, fill_factor : usize) -> Box<Line<Coord>> {
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
 
-        // Use a greedy algorithm. Completly fill the first node and put the remaining elements in
-        // the second node.
-        // The box can be splitted either vertically or horizontally.         
-        // Compute the number of tiles to be splited for each case and chose the case which leads to
-        // the minimal number of split to minimize the height of the tree
+impl<'a, Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> ExactSizeIterator for Iter<'a, Coord, Data, S> { }
 
-        // Clone the tile sets two times to compute the area for both case without cloning the
-        // whole subtrees.
-        let mut tile_set_vertical = tile_set; 
-        let mut tile_set_horizontal = tile_set_vertical.clone();
+/// A mutable iterator over the entries of an `RTree`, created by `RTree::iter_mut`
+pub struct IterMut<'a, Coord : Coordinate + 'a, Data : Debug + 'a> {
+    stack : Vec<&'a mut Node<Coord, Data>>,
+    remaining : usize,
+}
 
+impl<'a, Coord : Coordinate, Data : Debug> Iterator for IterMut<'a, Coord, Data> {
+    type Item = (Tile<Coord>, &'a mut Data);
+
+    fn next(&mut self) -> Option<(Tile<Coord>, &'a mut Data)> {
+        while let Some(node) = self.stack.pop() {
+            match *node {
+                Node::Leaf { ref tile, ref mut data, .. } => {
+                    self.remaining -= 1;
+                    return Some((*tile, data))
+                },
+                Node::Fragment { .. } => (),
+                Node::Node { ref mut vector, .. } => self.stack.extend(vector.iter_mut().rev().map(|child| &mut **child)),
+            }
+        }
+        None
+    }
 
-        // Sort by x axis starting points
-        tile_set_vertical.sort_by(|n1, n2| n1.bottom_left_corner().vertical_cmp(n2.bottom_left_corner()));
-        let iter = tile_set_vertical.iter();
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
 
-        // Compute the vertical splitting line
-        let corner =
-                tile_set_vertical[fill_factor - 1].bottom_left_corner();
-        let vline =
-            VerticalLine::at_point(corner);
+impl<'a, Coord : Coordinate, Data : Debug> ExactSizeIterator for IterMut<'a, Coord, Data> { }
 
-        // Compute the number of tiles which have to be splited (overlapping the first box)
-        let vertical_cost = iter.filter(|&tile| &vline >= tile).count();
+/// An owning iterator over the entries of an `RTree`, created by `RTree::into_iter`
+pub struct IntoIter<Coord : Coordinate, Data : Debug, S : Summary<Coord, Data> = ()> {
+    stack : Vec<Node<Coord, Data, S>>,
+    remaining : usize,
+}
 
+impl<Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> Iterator for IntoIter<Coord, Data, S> {
+    type Item = (Tile<Coord>, Data);
+
+    fn next(&mut self) -> Option<(Tile<Coord>, Data)> {
+        while let Some(node) = self.stack.pop() {
+            match node {
+                Node::Leaf { tile, data, .. } => {
+                    self.remaining -= 1;
+                    return Some((tile, data))
+                },
+                Node::Fragment { .. } => (),
+                Node::Node { vector, .. } => self.stack.extend(vector.into_iter().rev().map(Child::into_node)),
+            }
+        }
+        None
+    }
 
-        // Sort by y axis starting points
-        tile_set_horizontal.sort_by(|n1, n2| n1.bottom_left_corner().horizontal_cmp(n2.bottom_left_corner()));
-        let iter = tile_set_horizontal.iter();
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
 
-        // Compute the spliting line
-        let corner =
-                tile_set_horizontal[fill_factor - 1].bottom_left_corner();
-        let hline =
-            HorizontalLine::at_point(corner);
+impl<Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> ExactSizeIterator for IntoIter<Coord, Data, S> { }
 
-        // Compute the number of tiles which have to be splited (overlapping the first box)
-        let horizontal_cost = iter.filter(|&tile| &hline >= tile).count();
+impl<Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> IntoIterator for RTree<Coord, Data, S> {
+    type Item = (Tile<Coord>, Data);
+    type IntoIter = IntoIter<Coord, Data, S>;
 
-        // Decide wether the bounding box should be splitted vertically or horizontally
-        if vertical_cost > horizontal_cost {
-            Box::new(hline)
-        } else {
-            Box::new(vline)
+    fn into_iter(self) -> IntoIter<Coord, Data, S> {
+        IntoIter {
+            stack : self.root.into_iter().collect(),
+            remaining : self.len,
         }
     }
+}
 
-    /// Recursivly splits a subtree into two. All subsubtree contained by the `left` tile go to the left subtree. 
-    /// Subsubtrees overlapping the `left` tile are splitted. Other subtrees go to the right subtree. 
-    ///
-    /// Returns the right subtree.
-    ///
-    fn partition<'a>(&mut self, line : &'a (Line<Coord> + 'a), fill_factor : usize) -> Option<Node<Coord, Value>> {
-        let (left, right) =
-            match *self {
-                Node::Leaf { .. } => return None,
-                Node::Node { vector : ref mut vec, .. } => 
-                {
-                    let mut left_vec = Vec::with_capacity(fill_factor);
-                    let mut right_vec = Vec::with_capacity(fill_factor);
-
-                    let mut vector = Vec::new();
-                    mem::swap(&mut vector, vec);
+impl<'a, Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> IntoIterator for &'a RTree<Coord, Data, S> {
+    type Item = (Tile<Coord>, &'a Data);
+    type IntoIter = Iter<'a, Coord, Data, S>;
 
-                    for mut node in vector {
-                        match node.partial_cmp(line) {
-                            Some(Ordering::Less) => left_vec.push(node),
-                            // The current node overlaps the split tile. Recursivly split the subtree
-                            Some(Ordering::Equal) => 
-                            {
-                                let right_subnode = node.partition(line, fill_factor);
-                                left_vec.push(node);
-                                if let Some(subnode) = right_subnode {
-                                    right_vec.push(subnode);
-                                }
-                            },
-                            _ => right_vec.push(node),
-                        }
-                    }
-                    let left_box = bounding_tile(left_vec.iter().map(|n| n.coverage())).unwrap();
-                    let right_node =
-                        if right_vec.len() == 1 {
-                            Some(right_vec.remove(0))
-                        } else {
-                            let right_box = bounding_tile(right_vec.iter().map(|n| n.coverage()));
-                            right_box.map(|tile| Node::Node { coverage : tile, vector: right_vec })
-                        };
-                    (Node::Node { coverage : left_box, vector : left_vec}, right_node)
-                }
-            };
+    fn into_iter(self) -> Iter<'a, Coord, Data, S> {
+        self.iter()
+    }
+}
 
-        *self = left;
+impl<'a, Coord : Coordinate, Data : Debug> IntoIterator for &'a mut RTree<Coord, Data> {
+    type Item = (Tile<Coord>, &'a mut Data);
+    type IntoIter = IterMut<'a, Coord, Data>;
 
-        return right
+    fn into_iter(self) -> IterMut<'a, Coord, Data> {
+        self.iter_mut()
     }
+}
 
+/// Creates an empty tree with the default configuration
+impl<Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> Default for RTree<Coord, Data, S> {
 
-    /// If the node children count is greater than the fill factor, split it and returns the new
-    /// node
-    fn split_node(&mut self, fill_factor : usize) -> Option<Node<Coord, Value>> {
-        let line = 
-            match *self {
-                Node::Leaf { .. } => return None,
-                Node::Node { ref mut vector, .. } => 
-                    if { vector.len() <= fill_factor } {
-                        return None
-                    } else {
-                        // The node is full and needs to be splitted
-                        let tile_set = vector.iter().map(|node| node.coverage()).collect::<Vec<_>>(); 
-                        // Compute the split line
-                        Node::<Coord, Value>::sweep(tile_set, fill_factor)
-                    }
-            };
-        // Recursivly split the subtree. This call needs to be moved out because self cannot be
-        // borrowed more than once at a time (borrowed at ref mut vector)
-        self.partition(&*line, fill_factor)
+    fn default() -> RTree<Coord, Data, S> {
+        RTreeBuilder::new().build()
     }
+}
 
-    /// Recursivly inserts a `Point` into the subtree. If a binding for this point already exists
-    /// in the tree, its value is replaced and the old value is returned. Else, `None` is returned.
-    ///
-    /// The method also returns the overflow subtree which has to be added to the upper level
-    fn insert(&mut self, point : Point<Coord>, mut value : Value, fill_factor : usize) -> (Option<Value>, Option<Node<Coord, Value>>) {
-        // The call graph of this function is weird, but again it's for lifetime 
-        let old_value = match *self {
-            // Node is a leaf, swap the value and returns the old one
-            Node::Leaf { ref mut data, point : ref p } if p == &point => { mem::swap(data, &mut value); return (Some(value), None) },
-            Node::Leaf { .. } => return (None, Some(Node::Leaf { point : point, data : value })),
-            Node::Node { ref mut vector, .. } =>
-            {
-                let (old_value, overflow) = 
-                {
-                    // Node is an internal node, recursivly call insert if a subtree contains the point
-                    // to insert.
-                    let subnode = 
-                        vector
-                        .iter_mut()
-                        .find(|entry| **entry >= point);
-                    if let Some(child) = subnode {
-                        let (val, node) = child.insert(point, value, fill_factor);
-                        (val, node)
-                    } else { 
-                        (None, Some(Node::Leaf { data : value, point : point }))
-                    }
-                };
-                if let Some(node) = overflow {
-                    // No subtree contains the point, insert it at the current level
-                    vector.push(node);
-                    old_value
-                } else {
-                    return (old_value, None)
-                }
-            }
-        };
-        (old_value, self.split_node(fill_factor))
+/// Builds the tree with `RTree::bulk_load`
+impl<Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> FromIterator<(Point<Coord>, Data)> for RTree<Coord, Data, S> {
+
+    fn from_iter<I : IntoIterator<Item = (Point<Coord>, Data)>>(iter : I) -> RTree<Coord, Data, S> {
+        RTreeBuilder::new().bulk_load(iter.into_iter().collect())
     }
+}
 
-    /// Recursivly search for a matching point, returns `None` if no point is found or a
-    /// reference to the value associated with the point
-    fn find(&self, point : Point<Coord>) -> Option<&Value> {
-        match *self {
-            Node::Leaf { ref data, point : ref p } if p == &point => Some(data),
-            Node::Leaf { .. } => None,
-            Node::Node { ref vector, .. } => 
-                vector
-                .iter()
-                .find(|entry| **entry >= point)
-                .and_then(|node| node.find(point)),
+impl<Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> Extend<(Point<Coord>, Data)> for RTree<Coord, Data, S> {
+
+    fn extend<I : IntoIterator<Item = (Point<Coord>, Data)>>(&mut self, iter : I) {
+        for (point, data) in iter {
+            self.insert(point, data);
         }
     }
+}
 
-    /// Recursivly search for a matching point, returns `None` if no point is found or a mutable
-    /// reference to the value associated with the point
-    fn find_mut(&mut self, point : Point<Coord>) -> Option<&mut Value> {
-        match *self {
-            Node::Leaf { ref mut data, point : ref p } if p == &point => Some(data),
-            Node::Leaf { .. } => None,
-            Node::Node { ref mut vector, .. } => 
-                vector
-                .iter_mut()
-                .find(|entry| **entry >= point)
-                .and_then(|node| node.find_mut(point)),
+/// Builds the tree by inserting the objects one by one
+impl<Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> FromIterator<(Tile<Coord>, Data)> for RTree<Coord, Data, S> {
+
+    fn from_iter<I : IntoIterator<Item = (Tile<Coord>, Data)>>(iter : I) -> RTree<Coord, Data, S> {
+        let mut rtree = RTreeBuilder::new().build();
+        rtree.extend(iter);
+        rtree
+    }
+}
+
+impl<Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> Extend<(Tile<Coord>, Data)> for RTree<Coord, Data, S> {
+
+    fn extend<I : IntoIterator<Item = (Tile<Coord>, Data)>>(&mut self, iter : I) {
+        for (tile, data) in iter {
+            self.insert_tile(tile, data);
         }
     }
 }
 
-/// A balanced tree storing points in a 2D plane
-#[derive(Debug)]
-pub struct RTree<Coord : Coordinate, Data : Debug> {
-    fill_factor : usize,
-    root : Option<Node<Coord, Data>>,
+/// Walks the objects of a subtree intersecting a tile, see `TileQuery`
+struct Intersecting<'a, Coord : Coordinate + 'a, Value : Debug + 'a, S : Summary<Coord, Value> + 'a, H : 'a> {
+    tile : Tile<Coord>,
+    stack : Vec<&'a Node<Coord, Value, S, H>>,
+    /// The root of the tree, to find the value of an object reached through a fragment
+    root : Option<&'a Node<Coord, Value, S, H>>,
 }
 
-impl<Coord : Coordinate, Data : Debug> RTree<Coord, Data> {
+impl<'a, Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>, H : Child<Coord, Value, S>> Intersecting<'a, Coord, Value, S, H> {
 
-    /// Creates a new empty `RTree`
-    pub fn new() -> RTree<Coord, Data> {
-        RTree {
-            fill_factor : 4,
-            root : None,
+    fn new(tile : Tile<Coord>, root : Option<&'a Node<Coord, Value, S, H>>) -> Intersecting<'a, Coord, Value, S, H> {
+        Intersecting {
+            tile : tile,
+            stack : root.into_iter().collect(),
+            root : root,
         }
     }
 
-    /// Creates a new empty `RTree` covering the given tile with an user defined radix
-    pub fn with_radix(radix : usize) -> RTree<Coord, Data> {
-        RTree {
-            fill_factor : radix,
-            root : None,
-        }
+    /// Returns true if the part `clip` of the object `tile` reports the object. The parts of an
+    /// object do not overlap, so only one of them contains the bottom left corner of the
+    /// intersection between the object and the queried tile.
+    fn reports(&self, tile : &Tile<Coord>, clip : &Tile<Coord>) -> bool {
+        let (corner, query) = (tile.bottom_left_corner(), self.tile.bottom_left_corner());
+        self.tile.intersects(clip) &&
+            clip.contains(&Point::new(max(corner.get_x(), query.get_x()), max(corner.get_y(), query.get_y())))
     }
+}
 
-    /// Inserts a point into the RTree, and returns the old value associated to this point.
-    ///
-    pub fn insert(&mut self, point : Point<Coord>, data : Data) -> Option<Data> {
-        if let Some(mut root) = self.root.take() {
-            let (ret_val, overflow) = root.insert(point, data, self.fill_factor);
-            if let Some(n) = overflow {
-                // Replace the root
-                let mut vector = Vec::with_capacity(self.fill_factor);
-                let tile = n.coverage().union(root.coverage()); 
-                vector.push(n);
-                vector.push(root);
-
-                self.root = Some(Node::Node { coverage : tile, vector : vector });
-            } else {
-                self.root = Some(root);
+impl<'a, Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>, H : Child<Coord, Value, S>> Iterator for Intersecting<'a, Coord, Value, S, H> {
+    type Item = (Tile<Coord>, &'a Value);
+
+    fn next(&mut self) -> Option<(Tile<Coord>, &'a Value)> {
+        while let Some(node) = self.stack.pop() {
+            match *node {
+                Node::Leaf { ref tile, ref clip, ref data } =>
+                    if self.reports(tile, clip) {
+                        return Some((*tile, data))
+                    },
+                Node::Fragment { ref tile, ref clip } =>
+                    if self.reports(tile, clip) {
+                        return Some((*tile, self.root.and_then(|root| root.find(tile)).unwrap()))
+                    },
+                Node::Node { ref coverage, ref vector, .. } =>
+                    if self.tile.intersects(coverage) {
+                        // Push in reverse order so that children are visited in order
+                        self.stack.extend(vector.iter().rev().map(|child| &**child));
+                    },
             }
-            ret_val
-        } else {
-            self.root = Some(Node::Leaf { point : point, data : data }); None
         }
+        None
     }
-    /// Recursivly search for a matching point, returns `None` if no point is found or a mutable
-    /// reference to the value associated with the point
-    #[inline]
-    pub fn find_mut(&mut self, point : Point<Coord>) -> Option<&mut Data> {
-        self.root.as_mut().and_then(|r| r.find_mut(point))
-    }
+}
 
-    /// Recursivly search for a matching point, returns `None` if no point is found or a
-    /// reference to the value associated with the point
-    #[inline]
-    pub fn find(&self, point : Point<Coord>) -> Option<&Data> {
-        self.root.as_ref().and_then(|r| r.find(point))
-    }
+/// An iterator over the entries of an `RTree` intersecting a tile, created by
+/// `RTree::query_tile`
+pub struct TileQuery<'a, Coord : Coordinate + 'a, Data : Debug + 'a, S : Summary<Coord, Data> + 'a = ()> {
+    query : Intersecting<'a, Coord, Data, S, Owned<Coord, Data, S>>,
+}
+
+impl<'a, Coord : Coordinate, Data : Debug, S : Summary<Coord, Data>> Iterator for TileQuery<'a, Coord, Data, S> {
+    type Item = (Tile<Coord>, &'a Data);
 
+    fn next(&mut self) -> Option<(Tile<Coord>, &'a Data)> {
+        self.query.next()
+    }
 }
 
 // Tests
+#[cfg(test)]
+use ::geometry::{VerticalLine, HorizontalLine};
+
+#[cfg(test)]
+fn assert_invariants<Coord : Coordinate, Data : Debug>(rtree : &RTree<Coord, Data>) {
+    if let Err(e) = rtree.check_invariants() {
+        panic!("{}\n{:?}", e, rtree);
+    }
+}
+
 #[test]
 #[should_panic="index out of bounds"]
 fn test_sweep_empty() {
-    let vec = Vec::new();
-    Node::<u16, u16>::sweep(vec, 0);
+    let vec : Vec<Tile<u16>> = Vec::new();
+    GreedySweep.split(&vec, 0);
 }
 
 #[test]
 #[should_panic="index out of bounds"]
 fn test_sweep_fill_factor_empty() {
     let vec = vec![Tile::new(Point::new(0u16, 0), Point::new(4, 3))];
-    Node::<u16, u16>::sweep(vec, 4);
+    GreedySweep.split(&vec, 4);
 }
 
 #[test]
@@ -348,11 +1703,11 @@ fn test_sweep_horizontal() {
         Tile::new(Point::new(0, 10), Point::new(0, 11)),
         ];
 
-    let line = Node::<u16, u16>::sweep(vec.clone(), 2);
+    let line = GreedySweep.split(&vec, 2).unwrap();
     assert!(line.is_horizontal());
-    assert!(*line >= vec[0].bottom_left_corner());
-    assert!(*line >= vec[1].bottom_left_corner());
-    assert!(*line <= vec[2].bottom_left_corner());
+    assert!(vec[0].bottom_left_corner().side_of(&*line) != Side::After);
+    assert!(vec[1].bottom_left_corner().side_of(&*line) != Side::After);
+    assert!(vec[2].bottom_left_corner().side_of(&*line) != Side::Before);
 }
 
 #[test]
@@ -363,12 +1718,11 @@ fn test_sweep_vertical() {
         Tile::new(Point::new(4, 4), Point::new(7, 11)),
         ];
 
-    let line = Node::<u16, u16>::sweep(vec.clone(), 2);
+    let line = GreedySweep.split(&vec, 2).unwrap();
     assert!(line.is_vertical());
-    println!("{:?}, {:?}", line, vec[0].bottom_left_corner());
-    assert!(*line >= vec[0].bottom_left_corner());
-    assert!(*line >= vec[1].bottom_left_corner());
-    assert!(*line <= vec[2].bottom_left_corner());
+    assert!(vec[0].bottom_left_corner().side_of(&*line) != Side::After);
+    assert!(vec[1].bottom_left_corner().side_of(&*line) != Side::After);
+    assert!(vec[2].bottom_left_corner().side_of(&*line) != Side::Before);
 }
 
 #[test]
@@ -376,31 +1730,33 @@ fn test_partition_depth1_vertical() {
 
     let mut vector = Vec::with_capacity(5);
 
-    vector.push(Node::Leaf{ point : Point::new(0, 0),data : ()});
-    vector.push(Node::Leaf{ point : Point::new(4, 9),data : ()});
-    vector.push(Node::Leaf{ point : Point::new(6, 0),data : ()});
-    vector.push(Node::Leaf{ point : Point::new(3, 10), data : ()});
-    vector.push(Node::Leaf{ point : Point::new(4, 5), data : ()});
+    vector.push(Owned(Node::leaf(Point::new(0, 0), ())));
+    vector.push(Owned(Node::leaf(Point::new(4, 9), ())));
+    vector.push(Owned(Node::leaf(Point::new(6, 0), ())));
+    vector.push(Owned(Node::leaf(Point::new(3, 10), ())));
+    vector.push(Owned(Node::leaf(Point::new(4, 5), ())));
 
     let mut node = Node::Node {
         coverage : Tile::new(Point::new(0, 0), Point::new(10, 10)),
         vector : vector,
+        summary : (),
     };
 
     let line = Box::new(VerticalLine::new(5u16));
 
-    let ret = node.partition(&*line as &Line<u16>, 4);
+    let ret = node.partition(&*line as &Line<u16>, 4, &GreedySweep);
 
-    assert_eq!(ret, Some(Node::Leaf{ point : Point::new(6, 0), data : () }));
+    assert_eq!(ret, (vec![], vec![Node::leaf(Point::new(6, 0), ())]));
 
     match node { 
         Node::Node { 
             ref coverage,
             ref vector,
+            ..
         } =>
         {
             assert!(vector.len() == 4);
-            assert!(vector.iter().all(|n| n <= &*line))
+            assert!(vector.iter().all(|n| n.coverage().side_of(&*line as &Line<u16>) != Side::After))
         }
 
         _ => panic!("Node became a leaf"),
@@ -413,31 +1769,33 @@ fn test_partition_depth1_horizontal() {
 
     let mut vector = Vec::with_capacity(5);
 
-    vector.push(Node::Leaf{ point : Point::new(0, 0),data : ()});
-    vector.push(Node::Leaf{ point : Point::new(5, 9),data : ()});
-    vector.push(Node::Leaf{ point : Point::new(6, 0),data : ()});
-    vector.push(Node::Leaf{ point : Point::new(3, 10), data : ()});
-    vector.push(Node::Leaf{ point : Point::new(5, 5), data : ()});
+    vector.push(Owned(Node::leaf(Point::new(0, 0), ())));
+    vector.push(Owned(Node::leaf(Point::new(5, 9), ())));
+    vector.push(Owned(Node::leaf(Point::new(6, 0), ())));
+    vector.push(Owned(Node::leaf(Point::new(3, 10), ())));
+    vector.push(Owned(Node::leaf(Point::new(5, 5), ())));
 
     let mut node = Node::Node {
         coverage : Tile::new(Point::new(0, 0), Point::new(10, 11)),
         vector : vector,
+        summary : (),
     };
 
     let line = Box::new(HorizontalLine::new(9u16));
 
-    let ret = node.partition(&*line as &Line<u16>, 4);
+    let ret = node.partition(&*line as &Line<u16>, 4, &GreedySweep);
 
-    assert_eq!(ret, Some(Node::Leaf{ point : Point::new(3, 10), data : () }));
+    assert_eq!(ret, (vec![], vec![Node::leaf(Point::new(3, 10), ())]));
 
     match node { 
         Node::Node { 
             ref coverage,
             ref vector,
+            ..
         } =>
         {
             assert!(vector.len() == 4);
-            assert!(vector.iter().all(|n| n <= &*line))
+            assert!(vector.iter().all(|n| n.coverage().side_of(&*line as &Line<u16>) != Side::After))
         }
 
         _ => panic!("Node became a leaf"),
@@ -462,7 +1820,7 @@ fn insert() {
     rtree.insert(Point::new(0, 10), ());
     rtree.insert(Point::new(3, 7), ());
 
-    println!("{:?}", rtree);
+    assert_invariants(&rtree);
 
     assert!(rtree.find(Point::new(1, 1)).is_some());
     assert!(rtree.find(Point::new(1, 2)).is_some());
@@ -477,3 +1835,370 @@ fn insert() {
     assert!(rtree.find(Point::new(3, 7)).is_some());
 
 }
+
+#[test]
+fn remove() {
+
+    let points = vec![(1, 1), (1, 2), (1, 4), (3, 4), (4, 4), (10, 10), (9, 10), (1, 10), (8, 6), (0, 10), (3, 7)];
+    let mut rtree = RTree::<u16, u16>::new();
+
+    for (i, &(x, y)) in points.iter().enumerate() {
+        rtree.insert(Point::new(x, y), i as u16);
+        assert_invariants(&rtree);
+    }
+
+    assert_eq!(rtree.remove(Point::new(5, 5)), None);
+    assert_invariants(&rtree);
+
+    for (i, &(x, y)) in points.iter().enumerate() {
+        assert_eq!(rtree.remove(Point::new(x, y)), Some(i as u16));
+        assert_invariants(&rtree);
+        assert_eq!(rtree.remove(Point::new(x, y)), None);
+        assert!(rtree.find(Point::new(x, y)).is_none());
+
+        for (j, &(x, y)) in points.iter().enumerate().skip(i + 1) {
+            assert_eq!(rtree.find(Point::new(x, y)), Some(&(j as u16)));
+        }
+    }
+
+    assert!(rtree.root.is_none());
+}
+
+#[test]
+fn remove_shrinks_coverage() {
+
+    let mut rtree = RTree::<u16, ()>::new();
+
+    for x in 0..20 {
+        rtree.insert(Point::new(x, x), ());
+    }
+    for x in 10..20 {
+        rtree.remove(Point::new(x, x));
+        assert_invariants(&rtree);
+    }
+
+    match rtree.root {
+        Some(Node::Node { ref coverage, .. }) => assert!(*coverage == Tile::new(Point::new(0, 0), Point::new(9, 9))),
+        _ => panic!("Root became a leaf"),
+    }
+}
+
+#[test]
+fn query_tile() {
+
+    let mut rtree = RTree::<u16, (u16, u16)>::new();
+
+    for x in 0..20 {
+        for y in 0..20 {
+            if (x * 7 + y * 3) % 5 == 0 {
+                rtree.insert(Point::new(x, y), (x, y));
+            }
+        }
+    }
+    assert_invariants(&rtree);
+
+    let tile = Tile::new(Point::new(3, 4), Point::new(11, 9));
+    let mut found = rtree.query_tile(&tile).map(|(p, &d)| { assert!(p.bottom_left_corner() == Point::new(d.0, d.1)); d }).collect::<Vec<_>>();
+    found.sort();
+
+    let mut expected = Vec::new();
+    for x in 3..12 {
+        for y in 4..10 {
+            if (x * 7 + y * 3) % 5 == 0 {
+                expected.push((x, y));
+            }
+        }
+    }
+    assert_eq!(found, expected);
+
+    let outside = Tile::new(Point::new(30, 30), Point::new(40, 40));
+    assert_eq!(rtree.query_tile(&outside).count(), 0);
+    assert_eq!(RTree::<u16, ()>::new().query_tile(&tile).count(), 0);
+}
+
+#[test]
+fn nearest() {
+
+    let mut rtree = RTree::<i32, ()>::new();
+    let mut points = Vec::new();
+
+    for x in 0..15 {
+        for y in 0..15 {
+            if (x * 5 + y * 11) % 7 == 0 {
+                rtree.insert(Point::new(x, y), ());
+                points.push(Point::new(x, y));
+            }
+        }
+    }
+    assert_invariants(&rtree);
+
+    let distance = |p1 : Point<i32>, p2 : Point<i32>| (p1.get_x() - p2.get_x()).pow(2) + (p1.get_y() - p2.get_y()).pow(2);
+
+    for &query in &[Point::new(0, 0), Point::new(7, 3), Point::new(20, -4), Point::new(14, 14)] {
+        let mut expected = points.iter().map(|&p| distance(p, query)).collect::<Vec<_>>();
+        expected.sort();
+
+        let (nearest, _) = rtree.nearest(query).unwrap();
+        assert_eq!(distance(nearest.bottom_left_corner(), query), expected[0]);
+
+        let k_nearest = rtree.k_nearest(query, 10).iter().map(|&(p, _)| distance(p.bottom_left_corner(), query)).collect::<Vec<_>>();
+        assert_eq!(k_nearest, &expected[..10]);
+    }
+
+    assert_eq!(rtree.k_nearest(Point::new(0, 0), points.len() + 5).len(), points.len());
+    assert!(RTree::<i32, ()>::new().nearest(Point::new(0, 0)).is_none());
+}
+
+#[test]
+fn nearest_large_coordinates() {
+
+    // The squared distances do not fit in the coordinate type
+    let mut rtree = RTree::<u16, ()>::new();
+    for &(x, y) in &[(300, 0), (0, 250), (255, 255), (65535, 65535)] {
+        rtree.insert(Point::new(x, y), ());
+    }
+    let order = rtree.k_nearest(Point::new(0, 0), 4).iter().map(|&(t, _)| t.bottom_left_corner()).collect::<Vec<_>>();
+    assert_eq!(order, vec![Point::new(0, 250), Point::new(300, 0), Point::new(255, 255), Point::new(65535, 65535)]);
+
+    let mut rtree = RTree::<i32, ()>::new();
+    for &(x, y) in &[(100000, 0), (0, -70000), (i32::min_value(), i32::max_value())] {
+        rtree.insert(Point::new(x, y), ());
+    }
+    let order = rtree.k_nearest(Point::new(0, 0), 3).iter().map(|&(t, _)| t.bottom_left_corner()).collect::<Vec<_>>();
+    assert_eq!(order, vec![Point::new(0, -70000), Point::new(100000, 0), Point::new(i32::min_value(), i32::max_value())]);
+}
+
+#[test]
+fn bulk_load() {
+
+    let mut entries = Vec::new();
+    for x in 0..40 {
+        for y in 0..25 {
+            if (x * 3 + y * 7) % 4 != 0 {
+                entries.push((Point::new(x, y), (x, y)));
+            }
+        }
+    }
+    // Points on a single line
+    for x in 0..40 {
+        entries.push((Point::new(x, 100), (x, 100)));
+    }
+    entries.push((Point::new(0, 100), (0, 0)));
+
+    for &radix in &[2, 3, 4, 9] {
+        let mut rtree = RTree::<u16, (u16, u16)>::bulk_load_with_radix(radix, entries.clone());
+        assert_invariants(&rtree);
+
+        assert_eq!(rtree.find(Point::new(0, 100)), Some(&(0, 0)));
+        for &(p, d) in entries.iter().filter(|e| e.0 != Point::new(0, 100)) {
+            assert_eq!(rtree.find(p), Some(&d));
+        }
+        assert!(rtree.find(Point::new(0, 0)).is_none());
+
+        rtree.insert(Point::new(0, 0), (0, 0));
+        assert_invariants(&rtree);
+        assert_eq!(rtree.find(Point::new(0, 0)), Some(&(0, 0)));
+    }
+
+    assert!(RTree::<u16, ()>::bulk_load(Vec::new()).root.is_none());
+}
+
+#[test]
+fn collection() {
+
+    let mut rtree = RTree::<u16, u16>::with_radix(3);
+    assert!(rtree.is_empty());
+
+    for x in 0..10 {
+        for y in 0..10 {
+            assert_eq!(rtree.insert(Point::new(x, y), x * 10 + y), None);
+            assert_invariants(&rtree);
+        }
+    }
+    assert_eq!(rtree.insert(Point::new(3, 3), 33), Some(33));
+    assert_eq!(rtree.len(), 100);
+    assert_eq!(rtree.iter().len(), 100);
+
+    rtree.remove(Point::new(3, 3));
+    rtree.remove(Point::new(3, 3));
+    assert_eq!(rtree.len(), 99);
+    assert_invariants(&rtree);
+
+    for (_, data) in &mut rtree {
+        *data += 1;
+    }
+    let mut entries = rtree.iter().map(|(p, &d)| (p.bottom_left_corner().get_x(), p.bottom_left_corner().get_y(), d)).collect::<Vec<_>>();
+    entries.sort();
+    assert_eq!(entries.len(), 99);
+    assert!(entries.iter().all(|&(x, y, d)| d == x * 10 + y + 1));
+
+    let collected = rtree.into_iter().collect::<RTree<u16, u16>>();
+    assert_eq!(collected.len(), 99);
+    assert_invariants(&collected);
+    assert_eq!(collected.find(Point::new(9, 9)), Some(&100));
+
+    let mut extended = RTree::<u16, u16>::new();
+    extended.extend(collected);
+    extended.extend(vec![(Point::new(3, 3), 0), (Point::new(9, 9), 0)]);
+    assert_eq!(extended.len(), 100);
+    assert_invariants(&extended);
+    assert_eq!(extended.find(Point::new(9, 9)), Some(&0));
+
+    extended.clear();
+    assert!(extended.is_empty());
+    assert_eq!(extended.iter().count(), 0);
+}
+
+#[test]
+fn insert_remove_invariants() {
+
+    for &radix in &[2, 3, 4, 5, 8] {
+        let mut rtree = RTree::<i32, i32>::with_radix(radix);
+
+        for i in 0..300 {
+            let (x, y) = ((i * 37) % 41, (i * 13) % 29 - 10);
+            rtree.insert(Point::new(x, y), i);
+            assert_invariants(&rtree);
+        }
+        for i in 0..300 {
+            let (x, y) = ((i * 23) % 41, (i * 13) % 29 - 10);
+            rtree.remove(Point::new(x, y));
+            assert_invariants(&rtree);
+        }
+    }
+}
+
+#[test]
+fn insert_depth() {
+
+    // Scattered points must not pile up in the upper levels : the depth stays logarithmic
+    let points = (0..5000).map(|i| Point::new((i * 7919) % 997, (i * 6151) % 991)).collect::<Vec<_>>();
+    let strategies : Vec<Arc<SplitStrategy<i32>>> = vec![Arc::new(GreedySweep), Arc::new(MedianSplit), Arc::new(CostSplit)];
+    for split in strategies {
+        let mut rtree = RTree::<i32, ()>::with_radix(8);
+        let mut entries = RTree::<i32, ()>::with_radix(8);
+        rtree.split = split.clone();
+        entries.split = split.clone();
+        for &point in &points {
+            rtree.insert(point, ());
+            entries.entry(point).or_insert(());
+        }
+        for tree in &[rtree, entries] {
+            assert_invariants(tree);
+            let depth = tree.stats().depth;
+            assert!(depth <= 10, "depth {} with {:?}", depth, split);
+        }
+    }
+}
+
+#[test]
+fn tile_objects() {
+
+    let mut rtree = RTree::<i32, &str>::with_radix(3);
+    let rug = Tile::new(Point::new(2, 2), Point::new(9, 6));
+    let puddle = Tile::new(Point::new(5, 0), Point::new(6, 8));
+
+    for x in 0..12 {
+        rtree.insert(Point::new(x, (x * 5) % 9), "dust");
+    }
+    assert_eq!(rtree.insert_tile(rug, "rug"), None);
+    assert_eq!(rtree.insert_tile(puddle, "puddle"), None);
+    assert_invariants(&rtree);
+    assert_eq!(rtree.len(), 14);
+    assert!(rtree.stats().fragments > 0);
+
+    assert_eq!(rtree.find_tile(&rug), Some(&"rug"));
+    assert_eq!(rtree.insert_tile(rug, "carpet"), Some("rug"));
+    assert_eq!(rtree.len(), 14);
+    assert_eq!(rtree.find(Point::new(2, 2)), None);
+
+    // Both objects are returned once, whatever the number of their parts
+    let mut found = rtree.query_point(Point::new(5, 4)).map(|(_, &d)| d).collect::<Vec<_>>();
+    found.sort();
+    assert_eq!(found, vec!["carpet", "puddle"]);
+    let found = rtree.query_tile(&Tile::new(Point::new(7, 7), Point::new(8, 20))).map(|(_, &d)| d).collect::<Vec<_>>();
+    assert_eq!(found, vec!["dust"]);
+    assert_eq!(rtree.iter().filter(|&(t, _)| t == rug).count(), 1);
+
+    let (nearest, &data) = rtree.nearest(Point::new(6, 10)).unwrap();
+    assert_eq!((nearest, data), (puddle, "puddle"));
+    // Clipped objects are returned once, from their closest part
+    let by_distance = rtree.k_nearest(Point::new(6, 10), 20);
+    assert_eq!(by_distance.len(), rtree.len());
+    assert_eq!(by_distance.iter().filter(|&&(t, _)| t == rug).count(), 1);
+    assert!(by_distance.windows(2).all(|w| w[0].0.squared_distance(Point::new(6, 10)) <= w[1].0.squared_distance(Point::new(6, 10))));
+
+    assert_eq!(rtree.remove_tile(&rug), Some("carpet"));
+    assert_eq!(rtree.remove_tile(&rug), None);
+    assert_invariants(&rtree);
+    assert_eq!(rtree.query_point(Point::new(3, 3)).count(), 0);
+    assert_eq!(rtree.len(), 13);
+}
+
+#[test]
+fn check_invariants() {
+
+    let mut rtree = RTree::<u16, ()>::new();
+    rtree.insert(Point::new(0, 0), ());
+    rtree.insert(Point::new(4, 4), ());
+    assert!(rtree.check_invariants().is_ok());
+
+    if let Some(Node::Node { ref mut coverage, ref mut vector, .. }) = rtree.root {
+        *coverage = Tile::new(Point::new(0, 0), Point::new(1, 1));
+        vector.push(Owned(Node::Node { coverage : Tile::new(Point::new(3, 3), Point::new(5, 5)), vector : Vec::new(), summary : () }));
+    }
+    rtree.len = 3;
+
+    let violations = rtree.check_invariants().unwrap_err().violations().to_vec();
+    assert_eq!(violations,
+               vec![Violation::OutsideParent { parent : Tile::new(Point::new(0, 0), Point::new(1, 1)), child : Tile::from_point(Point::new(4, 4)) },
+               Violation::Overlap { first : Tile::from_point(Point::new(4, 4)), second : Tile::new(Point::new(3, 3), Point::new(5, 5)) },
+               Violation::OutsideParent { parent : Tile::new(Point::new(0, 0), Point::new(1, 1)), child : Tile::new(Point::new(3, 3), Point::new(5, 5)) },
+               Violation::EmptyNode { coverage : Tile::new(Point::new(3, 3), Point::new(5, 5)) },
+               Violation::WrongLength { len : 3, count : 2 },
+               ]);
+}
+
+/// A split strategy never separating the children of a node
+#[cfg(test)]
+#[derive(Debug)]
+struct NoSplit;
+
+#[cfg(test)]
+impl<Coord : Coordinate> SplitStrategy<Coord> for NoSplit {
+    fn split(&self, _ : &[Tile<Coord>], _ : usize) -> Option<Box<Line<Coord>>> {
+        None
+    }
+}
+
+#[test]
+fn check_overflow_with_tree_strategy() {
+    // Overflowing nodes are valid when the split strategy of the tree cannot separate them
+    let mut rtree = RTreeBuilder::new().split_strategy(NoSplit).build::<(), ()>();
+    for i in 0..10 {
+        rtree.insert(Point::new(i, i), ());
+    }
+    assert!(rtree.check_invariants().is_ok());
+
+    rtree.split = Arc::new(GreedySweep);
+    let violations = rtree.check_invariants().unwrap_err().violations().to_vec();
+    assert_eq!(violations, vec![Violation::Overflow { coverage : Tile::new(Point::new(0, 0), Point::new(9, 9)), children : 10 }]);
+}
+
+#[test]
+fn check_incomplete_object() {
+    let tile = Tile::new(Point::new(0u64, 0), Point::new(u64::max_value(), 1));
+    let mut rtree = RTree::<u64, ()>::new();
+    rtree.insert_tile(tile, ());
+    assert!(rtree.check_invariants().is_ok());
+
+    // The clip misses a single cell of the object, which an area in f64 does not see
+    if let Some(Node::Leaf { ref mut clip, .. }) = rtree.root {
+        *clip = Tile::new(Point::new(0, 0), Point::new(u64::max_value() - 1, 1));
+    }
+    let violations = rtree.check_invariants().unwrap_err().violations().to_vec();
+    assert_eq!(violations, vec![Violation::Incomplete { tile : tile }]);
+}
+
+#[cfg(test)]
+mod differential;
@@ -0,0 +1,508 @@
+//! A compact binary layout of the `RTree`, preserving its node structure so that a saved tree is
+//! loaded without inserting its objects again.
+//!
+//! The layout starts with the `MAGIC` bytes, the format `VERSION`, the fill factor and the number
+//! of objects, followed by the nodes in depth first order. Each node starts with a tag byte :
+//!
+//!  - `LEAF` : the object tile and the value, for a leaf holding the whole object
+//!  - `CLIPPED_LEAF` : the object tile, the clip and the value
+//!  - `FRAGMENT` : the object tile and the clip
+//!  - `NODE` : the coverage, the number of children and the children
+//!
+//! Tiles are written as their bottom left then top right corner. Numbers are little endian. The
+//! summaries are not saved, they are computed while loading the tree.
+//!
+//! Loading checks that every node coverage contains its children, and that the nodes are not
+//! nested deeper than `max_depth` allows for the saved number of objects, since the other methods
+//! of the tree walk it recursively.
+use std::fmt::Debug;
+use std::io::{
+    self,
+    ErrorKind,
+    Read,
+    Write,
+};
+
+use ::geometry::{
+    Coordinate,
+    Point,
+    Tile,
+    Contains,
+};
+use super::{
+    Child,
+    Node,
+    RTree,
+    RTreeBuilder,
+    Summary,
+};
+
+const MAGIC : &'static [u8; 5] = b"RTREE";
+const VERSION : u8 = 1;
+
+const EMPTY : u8 = 0;
+const LEAF : u8 = 1;
+const FRAGMENT : u8 = 2;
+const NODE : u8 = 3;
+const CLIPPED_LEAF : u8 = 4;
+
+/// A type with a binary representation, used to save the coordinates and the values of a tree
+pub trait Encode : Sized {
+
+    /// Writes the value
+    fn encode<W : Write>(&self, writer : &mut W) -> io::Result<()>;
+
+    /// Reads a value written by `encode`
+    fn decode<R : Read>(reader : &mut R) -> io::Result<Self>;
+}
+
+macro_rules! encode_number {
+    ($($t : ty),*) => {
+        $(
+            impl Encode for $t {
+                fn encode<W : Write>(&self, writer : &mut W) -> io::Result<()> {
+                    writer.write_all(&self.to_le_bytes())
+                }
+
+                fn decode<R : Read>(reader : &mut R) -> io::Result<$t> {
+                    let mut bytes = [0; ::std::mem::size_of::<$t>()];
+                    try!(reader.read_exact(&mut bytes));
+                    Ok(<$t>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    }
+}
+
+encode_number!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+/// Sizes are written on 64 bits, whatever the platform
+impl Encode for usize {
+
+    fn encode<W : Write>(&self, writer : &mut W) -> io::Result<()> {
+        (*self as u64).encode(writer)
+    }
+
+    fn decode<R : Read>(reader : &mut R) -> io::Result<usize> {
+        let size = try!(u64::decode(reader));
+        if size > usize::max_value() as u64 {
+            return Err(invalid_data("size too large for this platform"))
+        }
+        Ok(size as usize)
+    }
+}
+
+impl Encode for isize {
+
+    fn encode<W : Write>(&self, writer : &mut W) -> io::Result<()> {
+        (*self as i64).encode(writer)
+    }
+
+    fn decode<R : Read>(reader : &mut R) -> io::Result<isize> {
+        let value = try!(i64::decode(reader));
+        if value > isize::max_value() as i64 || value < isize::min_value() as i64 {
+            return Err(invalid_data("value too large for this platform"))
+        }
+        Ok(value as isize)
+    }
+}
+
+impl Encode for bool {
+
+    fn encode<W : Write>(&self, writer : &mut W) -> io::Result<()> {
+        (*self as u8).encode(writer)
+    }
+
+    fn decode<R : Read>(reader : &mut R) -> io::Result<bool> {
+        match try!(u8::decode(reader)) {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid_data("invalid boolean")),
+        }
+    }
+}
+
+impl Encode for () {
+
+    fn encode<W : Write>(&self, _ : &mut W) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn decode<R : Read>(_ : &mut R) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Strings are written as their length followed by their UTF-8 bytes
+impl Encode for String {
+
+    fn encode<W : Write>(&self, writer : &mut W) -> io::Result<()> {
+        try!(self.len().encode(writer));
+        writer.write_all(self.as_bytes())
+    }
+
+    fn decode<R : Read>(reader : &mut R) -> io::Result<String> {
+        let len = try!(usize::decode(reader));
+        let mut bytes = Vec::new();
+        try!(reader.take(len as u64).read_to_end(&mut bytes));
+        if bytes.len() < len {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "truncated string"))
+        }
+        String::from_utf8(bytes).map_err(|_| invalid_data("invalid UTF-8 string"))
+    }
+}
+
+impl<A : Encode, B : Encode> Encode for (A, B) {
+
+    fn encode<W : Write>(&self, writer : &mut W) -> io::Result<()> {
+        try!(self.0.encode(writer));
+        self.1.encode(writer)
+    }
+
+    fn decode<R : Read>(reader : &mut R) -> io::Result<(A, B)> {
+        let a = try!(A::decode(reader));
+        let b = try!(B::decode(reader));
+        Ok((a, b))
+    }
+}
+
+impl<Coord : Coordinate + Encode> Encode for Tile<Coord> {
+
+    fn encode<W : Write>(&self, writer : &mut W) -> io::Result<()> {
+        let (bottom, top) = (self.bottom_left_corner(), self.top_right_corner());
+        try!(bottom.get_x().encode(writer));
+        try!(bottom.get_y().encode(writer));
+        try!(top.get_x().encode(writer));
+        top.get_y().encode(writer)
+    }
+
+    fn decode<R : Read>(reader : &mut R) -> io::Result<Tile<Coord>> {
+        let bottom = Point::new(try!(Coord::decode(reader)), try!(Coord::decode(reader)));
+        let top = Point::new(try!(Coord::decode(reader)), try!(Coord::decode(reader)));
+        Tile::try_new(bottom, top).map_err(|_| invalid_data("tile corners in the wrong order"))
+    }
+}
+
+fn invalid_data(message : &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Returns the deepest nesting of nodes accepted when loading a tree of `len` objects.
+///
+/// A tree packed with `fill_factor` children per node has `levels` levels. Insertions, removals
+/// and the nodes split around clipped objects make trees a few times deeper, so a generous
+/// multiple of it is allowed, while keeping the recursive methods far from the stack limit.
+fn max_depth(len : usize, fill_factor : usize) -> usize {
+    let mut levels = 1;
+    let mut capacity = fill_factor;
+    while capacity < len {
+        capacity = capacity.saturating_mul(fill_factor);
+        levels += 1;
+    }
+    8 * levels + 8
+}
+
+impl<Coord, Value, S> Node<Coord, Value, S>
+where Coord : Coordinate + Encode, Value : Debug + Encode, S : Summary<Coord, Value>
+{
+
+    /// Recursivly writes the subtree in depth first order
+    fn encode<W : Write>(&self, writer : &mut W) -> io::Result<()> {
+        match *self {
+            Node::Leaf { ref tile, ref clip, ref data } if tile == clip => {
+                try!(LEAF.encode(writer));
+                try!(tile.encode(writer));
+                data.encode(writer)
+            },
+            Node::Leaf { ref tile, ref clip, ref data } => {
+                try!(CLIPPED_LEAF.encode(writer));
+                try!(tile.encode(writer));
+                try!(clip.encode(writer));
+                data.encode(writer)
+            },
+            Node::Fragment { ref tile, ref clip } => {
+                try!(FRAGMENT.encode(writer));
+                try!(tile.encode(writer));
+                clip.encode(writer)
+            },
+            Node::Node { ref coverage, ref vector, .. } => {
+                try!(NODE.encode(writer));
+                try!(coverage.encode(writer));
+                try!(vector.len().encode(writer));
+                for child in vector {
+                    try!(child.encode(writer));
+                }
+                Ok(())
+            },
+        }
+    }
+
+    /// Reads a tree written by `encode`, whose root tag was already read, counting its leaves
+    /// into `leaves`. The internal nodes being read are kept on a stack rather than in recursive
+    /// calls, so that corrupted data nesting nodes deeply cannot overflow the call stack, and
+    /// nodes nested deeper than `max_depth` are rejected.
+    fn decode<R : Read>(mut tag : u8, reader : &mut R, max_depth : usize, leaves : &mut usize) -> io::Result<Node<Coord, Value, S>> {
+        // The coverage, the number of children and the children read so far of each open node
+        let mut parents = Vec::new();
+        loop {
+            let mut node =
+                match tag {
+                    LEAF => {
+                        let tile = try!(Tile::decode(reader));
+                        *leaves += 1;
+                        Node::Leaf { tile : tile, clip : tile, data : try!(Value::decode(reader)) }
+                    },
+                    CLIPPED_LEAF => {
+                        let tile = try!(Tile::decode(reader));
+                        let clip = try!(Tile::decode(reader));
+                        *leaves += 1;
+                        Node::Leaf { tile : tile, clip : clip, data : try!(Value::decode(reader)) }
+                    },
+                    FRAGMENT => {
+                        let tile = try!(Tile::decode(reader));
+                        Node::Fragment { tile : tile, clip : try!(Tile::decode(reader)) }
+                    },
+                    NODE => {
+                        let coverage = try!(Tile::decode(reader));
+                        let len = try!(usize::decode(reader));
+                        if len == 0 {
+                            return Err(invalid_data("empty node"))
+                        }
+                        if parents.len() + 1 >= max_depth {
+                            return Err(invalid_data("nodes nested too deeply"))
+                        }
+                        // The length is not trusted to preallocate the children
+                        parents.push((coverage, len, Vec::new()));
+                        tag = try!(u8::decode(reader));
+                        continue
+                    },
+                    _ => return Err(invalid_data("unknown node tag")),
+                };
+            // Adds the node to its parent, closing the parents whose children are all read
+            loop {
+                let complete =
+                    match parents.last_mut() {
+                        Some(&mut (ref coverage, len, ref mut vector)) => {
+                            if !coverage.contains(&node.coverage()) {
+                                return Err(invalid_data("node outside of its parent coverage"))
+                            }
+                            vector.push(Child::new(node));
+                            vector.len() == len
+                        },
+                        None => return Ok(node),
+                    };
+                if !complete {
+                    break
+                }
+                let (coverage, _, vector) = parents.pop().unwrap();
+                node = Node::node(coverage, vector);
+            }
+            tag = try!(u8::decode(reader));
+        }
+    }
+}
+
+impl<Coord, Data, S> RTree<Coord, Data, S>
+where Coord : Coordinate + Encode, Data : Debug + Encode, S : Summary<Coord, Data>
+{
+
+    /// Writes the tree in a compact binary layout, preserving its nodes. The tree can be loaded
+    /// back with `RTree::read_from`.
+    pub fn write_to<W : Write>(&self, writer : &mut W) -> io::Result<()> {
+        try!(writer.write_all(MAGIC));
+        try!(VERSION.encode(writer));
+        try!(self.fill_factor.encode(writer));
+        try!(self.len.encode(writer));
+        match self.root {
+            Some(ref root) => root.encode(writer),
+            None => EMPTY.encode(writer),
+        }
+    }
+}
+
+impl<Coord, Data> RTree<Coord, Data>
+where Coord : Coordinate + Encode, Data : Debug + Encode
+{
+
+    /// Reads a tree written by `RTree::write_to`, splitting its nodes with the default strategy.
+    /// Fails with `ErrorKind::InvalidData` if the data is not a saved tree.
+    pub fn read_from<R : Read>(reader : &mut R) -> io::Result<RTree<Coord, Data>> {
+        RTreeBuilder::new().read_from(reader)
+    }
+}
+
+impl<Coord : Coordinate + Encode> RTreeBuilder<Coord> {
+
+    /// Reads a tree written by `RTree::write_to`, see `RTree::read_from`. The fill factor is the
+    /// one of the saved tree, only the split strategy of the builder is used.
+    pub fn read_from<Data, S, R>(self, reader : &mut R) -> io::Result<RTree<Coord, Data, S>>
+    where Data : Debug + Encode, S : Summary<Coord, Data>, R : Read
+    {
+        let mut magic = [0; 5];
+        try!(reader.read_exact(&mut magic));
+        if magic != *MAGIC {
+            return Err(invalid_data("not a saved RTree"))
+        }
+        if try!(u8::decode(reader)) != VERSION {
+            return Err(invalid_data("unsupported RTree format version"))
+        }
+        let fill_factor = try!(usize::decode(reader));
+        if fill_factor < 2 {
+            return Err(invalid_data("fill factor below 2"))
+        }
+        let len = try!(usize::decode(reader));
+
+        let mut leaves = 0;
+        let root =
+            match try!(u8::decode(reader)) {
+                EMPTY => None,
+                tag => Some(try!(Node::decode(tag, reader, max_depth(len, fill_factor), &mut leaves))),
+            };
+        if leaves != len {
+            return Err(invalid_data("wrong number of objects"))
+        }
+        Ok(RTree {
+            fill_factor : fill_factor,
+            split : self.split,
+            len : len,
+            root : root,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::ErrorKind;
+    use ::geometry::{Point, Tile};
+    use super::super::{RTree, RTreeBuilder, Count, MedianSplit, CostSplit};
+
+    fn sample() -> RTree<i32, (u32, String)> {
+        let mut rtree = RTree::with_radix(3);
+        for i in 0..80 {
+            rtree.insert(Point::new((i * 7) % 29, (i * 13) % 31), (i as u32, format!("dust {}", i)));
+        }
+        rtree.insert_tile(Tile::new(Point::new(2, 3), Point::new(20, 5)), (100, "rug".to_string()));
+        rtree.insert_tile(Tile::new(Point::new(-4, -4), Point::new(-1, 40)), (101, String::new()));
+        rtree
+    }
+
+    #[test]
+    fn round_trip() {
+        let rtree = sample();
+        let mut bytes = Vec::new();
+        rtree.write_to(&mut bytes).unwrap();
+
+        let loaded = RTree::<i32, (u32, String)>::read_from(&mut &bytes[..]).unwrap();
+        assert!(loaded.check_invariants().is_ok());
+        assert_eq!(loaded.len(), rtree.len());
+        // The nodes are the same, not only the objects
+        assert_eq!(loaded.to_dot(), rtree.to_dot());
+        assert_eq!(loaded.find_tile(&Tile::new(Point::new(2, 3), Point::new(20, 5))), Some(&(100, "rug".to_string())));
+
+        let mut saved_again = Vec::new();
+        loaded.write_to(&mut saved_again).unwrap();
+        assert_eq!(saved_again, bytes);
+    }
+
+    #[test]
+    fn summaries_and_strategy() {
+        let mut bytes = Vec::new();
+        sample().write_to(&mut bytes).unwrap();
+
+        let mut loaded = RTreeBuilder::new().split_strategy(MedianSplit).read_from::<(u32, String), Count, _>(&mut &bytes[..]).unwrap();
+        let all = Tile::new(Point::new(-10, -10), Point::new(50, 50));
+        assert_eq!(loaded.aggregate(&all), Count(82));
+        loaded.insert(Point::new(100, 100), (0, String::new()));
+        assert_eq!(loaded.aggregate(&all), Count(82));
+        assert!(loaded.check_invariants().is_ok());
+
+        let mut bytes = Vec::new();
+        RTree::<u16, ()>::new().write_to(&mut bytes).unwrap();
+        assert!(RTree::<u16, ()>::read_from(&mut &bytes[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn unclipped_leaves() {
+        // The leaf of a point is written without its clip : header, tag and tile
+        let mut rtree = RTree::<u16, ()>::new();
+        rtree.insert(Point::new(3, 4), ());
+        let mut bytes = Vec::new();
+        rtree.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 5 + 1 + 8 + 8 + 1 + 8);
+        assert_eq!(RTree::<u16, ()>::read_from(&mut &bytes[..]).unwrap().find(Point::new(3, 4)), Some(&()));
+    }
+
+    #[test]
+    fn invalid_data() {
+        let mut bytes = Vec::new();
+        sample().write_to(&mut bytes).unwrap();
+
+        let read = |bytes : &[u8]| RTree::<i32, (u32, String)>::read_from(&mut &bytes[..]).unwrap_err().kind();
+        assert_eq!(read(&bytes[..bytes.len() - 1]), ErrorKind::UnexpectedEof);
+        assert_eq!(read(b"RTRE"), ErrorKind::UnexpectedEof);
+        assert_eq!(read(b"GIF89a"), ErrorKind::InvalidData);
+
+        let mut version = bytes.clone();
+        version[5] = super::VERSION + 1;
+        assert_eq!(read(&version), ErrorKind::InvalidData);
+        for &fill_factor in &[0, 1] {
+            let mut radix = bytes.clone();
+            radix[5 + 1] = fill_factor;
+            assert_eq!(read(&radix), ErrorKind::InvalidData);
+        }
+        let mut tag = bytes.clone();
+        tag[5 + 1 + 8 + 8] = 9;
+        assert_eq!(read(&tag), ErrorKind::InvalidData);
+        let mut len = bytes.clone();
+        len[5 + 1 + 8] += 1;
+        assert_eq!(read(&len), ErrorKind::InvalidData);
+        // The root coverage shrunk to its bottom left corner no longer contains its children
+        let mut coverage = bytes.clone();
+        let root = 5 + 1 + 8 + 8 + 1;
+        let bottom = coverage[root..root + 8].to_vec();
+        coverage[root + 8..root + 16].copy_from_slice(&bottom);
+        assert_eq!(read(&coverage), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn deep_trees() {
+        // Trees built by insertion with the smallest fill factor are deeper than packed ones
+        for split in 0..3 {
+            let builder = RTreeBuilder::new().radix(2);
+            let builder =
+                match split {
+                    0 => builder,
+                    1 => builder.split_strategy(MedianSplit),
+                    _ => builder.split_strategy(CostSplit),
+                };
+            let mut rtree = builder.build::<(), ()>();
+            for i in 0..2000 {
+                rtree.insert(Point::new((i * 37) % 101, (i * 53) % 89), ());
+                rtree.insert_tile(Tile::new(Point::new(i % 97, i % 83), Point::new(i % 97 + 3, i % 83 + 1)), ());
+            }
+            for i in 0..1900 {
+                rtree.remove(Point::new((i * 37) % 101, (i * 53) % 89));
+            }
+            let mut bytes = Vec::new();
+            rtree.write_to(&mut bytes).unwrap();
+            let loaded = RTree::<i32, ()>::read_from(&mut &bytes[..]).unwrap();
+            assert_eq!(loaded.stats().depth, rtree.stats().depth);
+        }
+    }
+
+    #[test]
+    fn deep_nesting() {
+        // A million nested nodes with a single child are rejected without overflowing
+        let mut bytes = Vec::new();
+        RTree::<u16, ()>::new().write_to(&mut bytes).unwrap();
+        bytes.truncate(5 + 1 + 8 + 8);
+        let coverage = [0, 0, 0, 0, 1, 0, 1, 0];
+        for _ in 0..1_000_000 {
+            bytes.push(super::NODE);
+            bytes.extend_from_slice(&coverage);
+            bytes.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+        }
+        let error = RTree::<u16, ()>::read_from(&mut &bytes[..]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
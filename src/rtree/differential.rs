@@ -0,0 +1,328 @@
+//! Differential property tests : random operation sequences are applied both to an `RTree` and to
+//! a reference `BTreeMap` keyed by the corners of the objects, and the results are compared after
+//! each operation. Failing sequences are shrunk before being reported.
+//!
+//! The number of generated sequences and the first seed can be set with the
+//! `RTREE_PROPTEST_CASES` and `RTREE_PROPTEST_SEED` environment variables.
+use std::collections::BTreeMap;
+use std::env;
+
+use ::geometry::{
+    Point,
+    Tile,
+};
+use super::{
+    RTree,
+    RTreeBuilder,
+    GreedySweep,
+    MedianSplit,
+    CostSplit,
+};
+
+/// The configuration of the tree under test
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Config {
+    radix : usize,
+    split : Split,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Split {
+    Greedy,
+    Median,
+    Cost,
+}
+
+impl Config {
+
+    fn build(self) -> RTree<i32, u32> {
+        let builder = RTreeBuilder::new().radix(self.radix);
+        match self.split {
+            Split::Greedy => builder.split_strategy(GreedySweep),
+            Split::Median => builder.split_strategy(MedianSplit),
+            Split::Cost => builder.split_strategy(CostSplit),
+        }.build()
+    }
+}
+
+/// An operation applied to both trees
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Insert(i32, i32, u32),
+    Find(i32, i32),
+    FindMut(i32, i32, u32),
+    /// Increments the value of a point through its entry, inserting it if absent
+    Upsert(i32, i32, u32),
+    Remove(i32, i32),
+    /// Inserts the tile of given bottom left corner, width and height
+    InsertTile(i32, i32, i32, i32, u32),
+    RemoveTile(i32, i32, i32, i32),
+    /// Lists the objects intersecting a tile
+    Query(i32, i32, i32, i32),
+}
+
+/// Returns the tile of given bottom left corner, width and height
+fn tile(x : i32, y : i32, w : i32, h : i32) -> Tile<i32> {
+    Tile::new(Point::new(x, y), Point::new(x + w, y + h))
+}
+
+/// Returns the key of an object in the reference map
+fn key(tile : &Tile<i32>) -> (i32, i32, i32, i32) {
+    let (bottom, top) = (tile.bottom_left_corner(), tile.top_right_corner());
+    (bottom.get_x(), bottom.get_y(), top.get_x(), top.get_y())
+}
+
+/// A xorshift pseudo random generator, good enough to generate test cases
+struct Rng {
+    state : u64,
+}
+
+impl Rng {
+
+    fn new(seed : u64) -> Rng {
+        // The state must not be zero
+        Rng { state : seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1 }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Returns a number in `[low, high)`
+    fn range(&mut self, low : i64, high : i64) -> i64 {
+        low + (self.next() % ((high - low) as u64)) as i64
+    }
+}
+
+/// Generates a random tree configuration and operation sequence. Coordinates are drawn from a
+/// small square most of the time so that objects collide, and from a larger one otherwise.
+fn generate(rng : &mut Rng) -> (Config, Vec<Op>) {
+    let radix = rng.range(2, 11) as usize;
+    let split = [Split::Greedy, Split::Median, Split::Cost][rng.range(0, 3) as usize];
+    let len = rng.range(0, 300) as usize;
+    let spread = if rng.range(0, 4) == 0 { 1000 } else { 12 };
+
+    let ops = (0..len).map(|_| {
+        let x = rng.range(-spread, spread) as i32;
+        let y = rng.range(-spread, spread) as i32;
+        let (w, h) = (rng.range(0, 5) as i32, rng.range(0, 5) as i32);
+        let value = rng.next() as u32;
+        match rng.range(0, 15) {
+            0 | 1 | 2 | 3 | 4 => Op::Insert(x, y, value),
+            5 | 6 => Op::Find(x, y),
+            7 => Op::FindMut(x, y, value),
+            8 => Op::Upsert(x, y, value),
+            9 => Op::Remove(x, y),
+            10 | 11 => Op::InsertTile(x, y, w, h, value),
+            12 => Op::RemoveTile(x, y, w, h),
+            _ => Op::Query(x, y, w * 2, h * 2),
+        }
+    }).collect();
+
+    (Config { radix : radix, split : split }, ops)
+}
+
+/// Applies the operations to an `RTree` and a `BTreeMap` and returns a description of the first
+/// difference or invariant violation.
+fn run(config : Config, ops : &[Op]) -> Result<(), String> {
+    let mut rtree = config.build();
+    let mut reference = BTreeMap::<(i32, i32, i32, i32), u32>::new();
+
+    for (step, &op) in ops.iter().enumerate() {
+        let (found, expected) =
+            match op {
+                Op::Insert(x, y, v) => (rtree.insert(Point::new(x, y), v), reference.insert((x, y, x, y), v)),
+                Op::Find(x, y) => (rtree.find(Point::new(x, y)).cloned(), reference.get(&(x, y, x, y)).cloned()),
+                Op::FindMut(x, y, v) => {
+                    let found = rtree.find_mut(Point::new(x, y)).map(|d| { let old = *d; *d = v; old });
+                    let expected = reference.get_mut(&(x, y, x, y)).map(|d| { let old = *d; *d = v; old });
+                    (found, expected)
+                },
+                Op::Upsert(x, y, v) => {
+                    let found = *rtree.entry(Point::new(x, y)).and_modify(|d| *d = d.wrapping_add(1)).or_insert(v);
+                    let expected = *reference.entry((x, y, x, y)).and_modify(|d| *d = d.wrapping_add(1)).or_insert(v);
+                    (Some(found), Some(expected))
+                },
+                Op::Remove(x, y) => (rtree.remove(Point::new(x, y)), reference.remove(&(x, y, x, y))),
+                Op::InsertTile(x, y, w, h, v) => {
+                    let tile = tile(x, y, w, h);
+                    (rtree.insert_tile(tile, v), reference.insert(key(&tile), v))
+                },
+                Op::RemoveTile(x, y, w, h) => {
+                    let tile = tile(x, y, w, h);
+                    (rtree.remove_tile(&tile), reference.remove(&key(&tile)))
+                },
+                Op::Query(x, y, w, h) => {
+                    let (x1, y1) = (x + w, y + h);
+                    let mut found = rtree.query_tile(&tile(x, y, w, h)).map(|(t, &d)| (key(&t), d)).collect::<Vec<_>>();
+                    found.sort();
+                    let expected =
+                        reference.iter()
+                        .filter(|&(&(a, b, c, d), _)| a <= x1 && x <= c && b <= y1 && y <= d)
+                        .map(|(&k, &d)| (k, d))
+                        .collect::<Vec<_>>();
+                    if found != expected {
+                        return Err(format!("step {} {:?} returned {:?} instead of {:?}", step, op, found, expected))
+                    }
+                    (None, None)
+                },
+            };
+
+        if found != expected {
+            return Err(format!("step {} {:?} returned {:?} instead of {:?}", step, op, found, expected))
+        }
+        if let Err(e) = rtree.check_invariants() {
+            return Err(format!("step {} {:?} : {}", step, op, e))
+        }
+        if rtree.len() != reference.len() {
+            return Err(format!("step {} {:?} : length is {} instead of {}", step, op, rtree.len(), reference.len()))
+        }
+    }
+
+    let mut entries = rtree.iter().map(|(t, &d)| (key(&t), d)).collect::<Vec<_>>();
+    entries.sort();
+    if entries != reference.into_iter().collect::<Vec<_>>() {
+        return Err(format!("iteration returned {:?}", entries))
+    }
+    Ok(())
+}
+
+/// Returns simpler versions of an operation, with coordinates and values closer to zero
+fn simplify(op : Op) -> Vec<Op> {
+    let smaller = |v : i32| if v == 0 { vec![] } else { vec![0, v / 2] };
+    let mut ops = Vec::new();
+    match op {
+        Op::Insert(x, y, v) => {
+            ops.extend(smaller(x).into_iter().map(|x| Op::Insert(x, y, v)));
+            ops.extend(smaller(y).into_iter().map(|y| Op::Insert(x, y, v)));
+            if v != 0 { ops.push(Op::Insert(x, y, 0)) }
+        },
+        Op::FindMut(x, y, v) => {
+            ops.push(Op::Find(x, y));
+            ops.extend(smaller(x).into_iter().map(|x| Op::FindMut(x, y, v)));
+            ops.extend(smaller(y).into_iter().map(|y| Op::FindMut(x, y, v)));
+        },
+        Op::Upsert(x, y, v) => {
+            ops.push(Op::Insert(x, y, v));
+            ops.extend(smaller(x).into_iter().map(|x| Op::Upsert(x, y, v)));
+            ops.extend(smaller(y).into_iter().map(|y| Op::Upsert(x, y, v)));
+        },
+        Op::Find(x, y) => {
+            ops.extend(smaller(x).into_iter().map(|x| Op::Find(x, y)));
+            ops.extend(smaller(y).into_iter().map(|y| Op::Find(x, y)));
+        },
+        Op::Remove(x, y) => {
+            ops.extend(smaller(x).into_iter().map(|x| Op::Remove(x, y)));
+            ops.extend(smaller(y).into_iter().map(|y| Op::Remove(x, y)));
+        },
+        Op::InsertTile(x, y, w, h, v) => {
+            ops.push(Op::Insert(x, y, v));
+            ops.extend(smaller(x).into_iter().map(|x| Op::InsertTile(x, y, w, h, v)));
+            ops.extend(smaller(y).into_iter().map(|y| Op::InsertTile(x, y, w, h, v)));
+            ops.extend(smaller(w).into_iter().map(|w| Op::InsertTile(x, y, w, h, v)));
+            ops.extend(smaller(h).into_iter().map(|h| Op::InsertTile(x, y, w, h, v)));
+        },
+        Op::RemoveTile(x, y, w, h) => {
+            ops.push(Op::Remove(x, y));
+            ops.extend(smaller(x).into_iter().map(|x| Op::RemoveTile(x, y, w, h)));
+            ops.extend(smaller(y).into_iter().map(|y| Op::RemoveTile(x, y, w, h)));
+            ops.extend(smaller(w).into_iter().map(|w| Op::RemoveTile(x, y, w, h)));
+            ops.extend(smaller(h).into_iter().map(|h| Op::RemoveTile(x, y, w, h)));
+        },
+        Op::Query(x, y, w, h) => {
+            ops.extend(smaller(x).into_iter().map(|x| Op::Query(x, y, w, h)));
+            ops.extend(smaller(y).into_iter().map(|y| Op::Query(x, y, w, h)));
+            ops.extend(smaller(w).into_iter().map(|w| Op::Query(x, y, w, h)));
+            ops.extend(smaller(h).into_iter().map(|h| Op::Query(x, y, w, h)));
+        },
+    }
+    ops
+}
+
+/// Shrinks a failing test case : removes chunks of operations, simplifies the remaining ones and
+/// lowers the fill factor as long as the property keeps failing.
+fn shrink<F>(mut config : Config, mut ops : Vec<Op>, property : F) -> (Config, Vec<Op>)
+where F : Fn(Config, &[Op]) -> Result<(), String>
+{
+    loop {
+        let mut progress = false;
+
+        // Remove chunks of decreasing size
+        let mut chunk = ops.len() / 2;
+        while chunk > 0 {
+            let mut start = 0;
+            while start + chunk <= ops.len() {
+                let mut candidate = ops.clone();
+                candidate.drain(start..start + chunk);
+                if property(config, &candidate).is_err() {
+                    ops = candidate;
+                    progress = true;
+                } else {
+                    start += chunk;
+                }
+            }
+            chunk /= 2;
+        }
+
+        // Simplify each operation
+        for i in 0..ops.len() {
+            for simpler in simplify(ops[i]) {
+                let mut candidate = ops.clone();
+                candidate[i] = simpler;
+                if property(config, &candidate).is_err() {
+                    ops = candidate;
+                    progress = true;
+                    break;
+                }
+            }
+        }
+
+        let smaller = Config { radix : config.radix - 1, .. config };
+        if config.radix > 2 && property(smaller, &ops).is_err() {
+            config = smaller;
+            progress = true;
+        }
+
+        if !progress {
+            return (config, ops)
+        }
+    }
+}
+
+fn env_or(name : &str, default : u64) -> u64 {
+    env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+#[test]
+fn rtree_matches_btreemap() {
+    let cases = env_or("RTREE_PROPTEST_CASES", 300);
+    let first_seed = env_or("RTREE_PROPTEST_SEED", 0);
+
+    for seed in first_seed..first_seed + cases {
+        let (config, ops) = generate(&mut Rng::new(seed));
+        if let Err(e) = run(config, &ops) {
+            let (config, ops) = shrink(config, ops, run);
+            panic!("seed {} failed : {}\nshrunk to {:?} and operations {:?} : {}",
+                   seed, e, config, ops, run(config, &ops).unwrap_err());
+        }
+    }
+}
+
+#[test]
+fn shrink_finds_minimal_case() {
+    // A property failing when two points are inserted on the same vertical line
+    let property = |_ : Config, ops : &[Op]| {
+        let xs = ops.iter().filter_map(|op| match *op { Op::Insert(x, _, _) => Some(x), _ => None }).collect::<Vec<_>>();
+        if xs.iter().enumerate().any(|(i, x)| xs[i + 1..].contains(x)) { Err(String::new()) } else { Ok(()) }
+    };
+
+    let ops = vec![Op::Find(1, 2), Op::Insert(5, 3, 7), Op::Remove(4, 4), Op::Insert(8, 1, 2), Op::Insert(5, 9, 1), Op::FindMut(3, 3, 3)];
+    let (config, ops) = shrink(Config { radix : 8, split : Split::Cost }, ops, property);
+
+    assert_eq!(config, Config { radix : 2, split : Split::Cost });
+    assert_eq!(ops, vec![Op::Insert(5, 0, 0), Op::Insert(5, 0, 0)]);
+}
+
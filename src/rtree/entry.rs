@@ -0,0 +1,246 @@
+//! Entries of an `RTree`, giving in place access to the value of a point with a single search.
+use std::fmt::Debug;
+use std::mem;
+
+use ::geometry::{
+    Coordinate,
+    Point,
+    Tile,
+};
+use super::{
+    Node,
+    RTree,
+};
+
+/// The entry of a point in an `RTree`, returned by `RTree::entry`
+#[derive(Debug)]
+pub enum Entry<'a, Coord : Coordinate + 'a, Data : Debug + 'a> {
+    Occupied(OccupiedEntry<'a, Coord, Data>),
+    Vacant(VacantEntry<'a, Coord, Data>),
+}
+
+/// The entry of a point present in the tree
+#[derive(Debug)]
+pub struct OccupiedEntry<'a, Coord : Coordinate + 'a, Data : Debug + 'a> {
+    tree : &'a mut RTree<Coord, Data>,
+    point : Point<Coord>,
+    /// Indices of the children leading from the root to the leaf of the point
+    path : Vec<usize>,
+}
+
+/// The entry of a point absent from the tree
+#[derive(Debug)]
+pub struct VacantEntry<'a, Coord : Coordinate + 'a, Data : Debug + 'a> {
+    tree : &'a mut RTree<Coord, Data>,
+    point : Point<Coord>,
+    /// Indices of the children leading from the root to the deepest node containing the point
+    path : Vec<usize>,
+}
+
+/// Returns the value of a leaf
+fn leaf_data<Coord : Coordinate, Data : Debug>(node : &Node<Coord, Data>) -> &Data {
+    match *node {
+        Node::Leaf { ref data, .. } => data,
+        _ => unreachable!("an occupied entry does not lead to a leaf"),
+    }
+}
+
+/// Returns the value of a leaf
+fn leaf_data_mut<Coord : Coordinate, Data : Debug>(node : &mut Node<Coord, Data>) -> &mut Data {
+    match *node {
+        Node::Leaf { ref mut data, .. } => data,
+        _ => unreachable!("an occupied entry does not lead to a leaf"),
+    }
+}
+
+impl<'a, Coord : Coordinate, Data : Debug> Entry<'a, Coord, Data> {
+
+    /// Returns the point of the entry
+    pub fn point(&self) -> Point<Coord> {
+        match *self {
+            Entry::Occupied(ref entry) => entry.point(),
+            Entry::Vacant(ref entry) => entry.point(),
+        }
+    }
+
+    /// Inserts `default` if the entry is vacant, and returns a mutable reference to the value
+    pub fn or_insert(self, default : Data) -> &'a mut Data {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Inserts the result of `default` if the entry is vacant, and returns a mutable reference to
+    /// the value
+    pub fn or_insert_with<F : FnOnce() -> Data>(self, default : F) -> &'a mut Data {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Calls `f` on the value if the entry is occupied
+    pub fn and_modify<F : FnOnce(&mut Data)>(mut self, f : F) -> Entry<'a, Coord, Data> {
+        if let Entry::Occupied(ref mut entry) = self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, Coord : Coordinate, Data : Debug> OccupiedEntry<'a, Coord, Data> {
+
+    pub(super) fn new(tree : &'a mut RTree<Coord, Data>, point : Point<Coord>, path : Vec<usize>) -> OccupiedEntry<'a, Coord, Data> {
+        OccupiedEntry { tree : tree, point : point, path : path }
+    }
+
+    /// Returns the point of the entry
+    pub fn point(&self) -> Point<Coord> {
+        self.point
+    }
+
+    /// Returns a reference to the value of the point
+    pub fn get(&self) -> &Data {
+        leaf_data(self.tree.root.as_ref().unwrap().descendant(&self.path))
+    }
+
+    /// Returns a mutable reference to the value of the point
+    pub fn get_mut(&mut self) -> &mut Data {
+        leaf_data_mut(self.tree.root.as_mut().unwrap().descendant_mut(&self.path))
+    }
+
+    /// Converts the entry into a mutable reference to the value of the point, bound to the
+    /// lifetime of the tree
+    pub fn into_mut(self) -> &'a mut Data {
+        leaf_data_mut(self.tree.root.as_mut().unwrap().descendant_mut(&self.path))
+    }
+
+    /// Replaces the value of the point and returns the old one
+    pub fn insert(&mut self, value : Data) -> Data {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the point from the tree and returns its value. The removal searches the tree again
+    /// to condense the nodes along the path.
+    pub fn remove(self) -> Data {
+        self.tree.remove(self.point).unwrap()
+    }
+}
+
+impl<'a, Coord : Coordinate, Data : Debug> VacantEntry<'a, Coord, Data> {
+
+    pub(super) fn new(tree : &'a mut RTree<Coord, Data>, point : Point<Coord>, path : Vec<usize>) -> VacantEntry<'a, Coord, Data> {
+        VacantEntry { tree : tree, point : point, path : path }
+    }
+
+    /// Returns the point of the entry
+    pub fn point(&self) -> Point<Coord> {
+        self.point
+    }
+
+    /// Inserts the point with the given value, and returns a mutable reference to the value
+    pub fn insert(self, value : Data) -> &'a mut Data {
+        let VacantEntry { tree, point, mut path } = self;
+        let leaf = Node::leaf(point, value);
+        tree.len += 1;
+
+        if let Some(mut root) = tree.root.take() {
+            // The path goes down the nodes growing to cover the point, as in `RTree::insert`
+            loop {
+                let index =
+                    match *root.descendant(&path) {
+                        Node::Node { ref vector, .. } => Node::growing_child(vector, &Tile::from_point(point), &[]),
+                        _ => None,
+                    };
+                match index {
+                    Some(index) => path.push(index),
+                    None => break,
+                }
+            }
+            // Without a split, the leaf is pushed after the children of the last node of the path
+            let children =
+                match *root.descendant(&path) {
+                    Node::Node { ref vector, .. } if vector.len() < tree.fill_factor => Some(vector.len()),
+                    _ => None,
+                };
+            let overflow = root.insert_at(&path, leaf, tree.fill_factor, &*tree.split);
+            tree.set_root(root, overflow);
+
+            if let Some(index) = children {
+                path.push(index);
+            } else {
+                // The nodes along the path were split, search the leaf again
+                path.clear();
+                tree.root.as_ref().unwrap().locate(&Tile::from_point(point), &mut path);
+            }
+        } else {
+            tree.root = Some(leaf);
+        }
+
+        leaf_data_mut(tree.root.as_mut().unwrap().descendant_mut(&path))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ::geometry::Point;
+    use super::super::RTree;
+    use super::Entry;
+
+    #[test]
+    fn count_points() {
+        let mut rtree = RTree::<i32, u32>::with_radix(3);
+        let points = (0..200).map(|i| Point::new((i * 7) % 23, (i * 11) % 13));
+
+        for point in points.clone() {
+            *rtree.entry(point).or_insert(0) += 1;
+            assert!(rtree.check_invariants().is_ok());
+        }
+
+        let mut expected = ::std::collections::BTreeMap::new();
+        for point in points {
+            *expected.entry((point.get_x(), point.get_y())).or_insert(0) += 1;
+        }
+        assert_eq!(rtree.len(), expected.len());
+        for ((x, y), count) in expected {
+            assert_eq!(rtree.find(Point::new(x, y)), Some(&count));
+        }
+    }
+
+    #[test]
+    fn occupied() {
+        let mut rtree = RTree::<i32, &str>::new();
+        for i in 0..10 {
+            rtree.insert(Point::new(i, -i), "dust");
+        }
+
+        match rtree.entry(Point::new(3, -3)) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.point(), Point::new(3, -3));
+                assert_eq!(entry.get(), &"dust");
+                assert_eq!(entry.insert("clean"), "dust");
+                assert_eq!(entry.remove(), "clean");
+            },
+            Entry::Vacant(_) => panic!("the point is in the tree"),
+        }
+        assert_eq!(rtree.find(Point::new(3, -3)), None);
+        assert_eq!(rtree.len(), 9);
+
+        rtree.entry(Point::new(4, -4)).and_modify(|d| *d = "rover").or_insert("none");
+        assert_eq!(rtree.find(Point::new(4, -4)), Some(&"rover"));
+    }
+
+    #[test]
+    fn vacant() {
+        let mut rtree = RTree::<i32, u32>::new();
+        match rtree.entry(Point::new(1, 1)) {
+            Entry::Vacant(entry) => *entry.insert(4) += 1,
+            Entry::Occupied(_) => panic!("the tree is empty"),
+        }
+        assert_eq!(rtree.find(Point::new(1, 1)), Some(&5));
+        assert_eq!(*rtree.entry(Point::new(2, 1)).or_insert_with(|| 7), 7);
+        assert_eq!(rtree.len(), 2);
+        assert!(rtree.check_invariants().is_ok());
+    }
+}
@@ -0,0 +1,139 @@
+//! Spatial join of two `RTree`s, finding the pairs of intersecting objects by descending both
+//! trees simultaneously.
+use std::fmt::Debug;
+
+use ::geometry::{
+    Coordinate,
+    Tile,
+    Contains,
+    Intersects,
+};
+use super::{
+    Node,
+    RTree,
+    Summary,
+};
+
+/// Returns a lazy iterator over all the pairs of objects of `a` and `b` which intersect, with
+/// their associated values. Pairs of subtrees whose coverages do not overlap are skipped, and each
+/// pair of objects is returned once even if the objects are clipped.
+pub fn join<'a, 'b, Coord, A, B, SA, SB>(a : &'a RTree<Coord, A, SA>, b : &'b RTree<Coord, B, SB>) -> Join<'a, 'b, Coord, A, B, SA, SB>
+where Coord : Coordinate, A : Debug, B : Debug, SA : Summary<Coord, A>, SB : Summary<Coord, B>
+{
+    let mut stack = Vec::new();
+    if let (Some(root_a), Some(root_b)) = (a.root.as_ref(), b.root.as_ref()) {
+        if root_a.coverage().intersects(&root_b.coverage()) {
+            stack.push((root_a, root_b));
+        }
+    }
+    Join {
+        root_a : a.root.as_ref(),
+        root_b : b.root.as_ref(),
+        stack : stack,
+    }
+}
+
+/// An iterator over the pairs of intersecting objects of two `RTree`s, created by `rtree::join`
+pub struct Join<'a, 'b, Coord, A, B, SA = (), SB = ()>
+where Coord : Coordinate + 'a, A : Debug + 'a, B : Debug + 'b, SA : Summary<Coord, A> + 'a, SB : Summary<Coord, B> + 'b
+{
+    /// The roots of the trees, to find the value of an object reached through a fragment
+    root_a : Option<&'a Node<Coord, A, SA>>,
+    root_b : Option<&'b Node<Coord, B, SB>>,
+    /// Pairs of subtrees with overlapping coverages still to visit
+    stack : Vec<(&'a Node<Coord, A, SA>, &'b Node<Coord, B, SB>)>,
+}
+
+/// Returns true if the parts `clip_a` and `clip_b` of the objects `tile_a` and `tile_b` report the
+/// pair. The parts of an object do not overlap, so only one pair of parts contains the bottom left
+/// corner of the intersection between the objects.
+fn reports<Coord : Coordinate>(tile_a : &Tile<Coord>, clip_a : &Tile<Coord>, tile_b : &Tile<Coord>, clip_b : &Tile<Coord>) -> bool {
+    match tile_a.intersection(tile_b) {
+        Some(common) => {
+            let corner = common.bottom_left_corner();
+            clip_a.contains(&corner) && clip_b.contains(&corner)
+        },
+        None => false,
+    }
+}
+
+impl<'a, 'b, Coord, A, B, SA, SB> Iterator for Join<'a, 'b, Coord, A, B, SA, SB>
+where Coord : Coordinate, A : Debug, B : Debug, SA : Summary<Coord, A>, SB : Summary<Coord, B>
+{
+    type Item = ((Tile<Coord>, &'a A), (Tile<Coord>, &'b B));
+
+    fn next(&mut self) -> Option<((Tile<Coord>, &'a A), (Tile<Coord>, &'b B))> {
+        while let Some((node_a, node_b)) = self.stack.pop() {
+            match (node_a, node_b) {
+                (&Node::Node { ref vector, .. }, _) => {
+                    // Descend into the first tree, the second one is expanded when a leaf of the
+                    // first one is reached
+                    let coverage = node_b.coverage();
+                    self.stack.extend(vector.iter().filter(|n| n.coverage().intersects(&coverage)).map(|n| (&**n, node_b)));
+                },
+                (_, &Node::Node { ref vector, .. }) => {
+                    let coverage = node_a.coverage();
+                    self.stack.extend(vector.iter().filter(|n| n.coverage().intersects(&coverage)).map(|n| (node_a, &**n)));
+                },
+                _ => {
+                    let (tile_a, tile_b) = (node_a.object().unwrap(), node_b.object().unwrap());
+                    if reports(&tile_a, &node_a.coverage(), &tile_b, &node_b.coverage()) {
+                        let data_a = self.root_a.and_then(|root| root.find(&tile_a)).unwrap();
+                        let data_b = self.root_b.and_then(|root| root.find(&tile_b)).unwrap();
+                        return Some(((tile_a, data_a), (tile_b, data_b)))
+                    }
+                },
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ::geometry::{Point, Tile, Intersects};
+    use super::super::RTree;
+    use super::join;
+
+    #[test]
+    fn dust_and_hazards() {
+        let mut dust = RTree::<i32, u32>::with_radix(3);
+        for i in 0..60 {
+            dust.insert(Point::new((i * 7) % 19, (i * 5) % 13), i as u32);
+        }
+        let mut hazards = RTree::<i32, &str>::with_radix(2);
+        hazards.insert_tile(Tile::new(Point::new(2, 2), Point::new(9, 6)), "rug");
+        hazards.insert_tile(Tile::new(Point::new(5, 0), Point::new(6, 12)), "puddle");
+        hazards.insert_tile(Tile::new(Point::new(15, 10), Point::new(30, 30)), "stairs");
+        hazards.insert(Point::new(0, 0), "socket");
+        hazards.insert(Point::new(-5, 3), "cable");
+
+        let mut found = join(&dust, &hazards).map(|((p, &d), (_, &h))| (p, d, h)).collect::<Vec<_>>();
+        found.sort_by_key(|&(_, d, h)| (d, h));
+
+        let mut expected = Vec::new();
+        for (p, &d) in &dust {
+            for (t, &h) in &hazards {
+                if p.intersects(&t) {
+                    expected.push((p, d, h));
+                }
+            }
+        }
+        expected.sort_by_key(|&(_, d, h)| (d, h));
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn clipped_objects() {
+        // Both trees are small enough to clip the objects, which must be reported once
+        let tiles = (0..12).map(|i| Tile::new(Point::new(i * 3, 0), Point::new(i * 3 + 4, 4))).collect::<Vec<_>>();
+        let a = tiles.iter().enumerate().map(|(i, &t)| (t, i)).collect::<RTree<i32, usize>>();
+        let b = vec![(Tile::new(Point::new(-1, 2), Point::new(40, 3)), ()), (Tile::new(Point::new(6, -3), Point::new(6, 8)), ())]
+            .into_iter().collect::<RTree<i32, ()>>();
+
+        assert_eq!(join(&a, &b).count(), 12 + 2);
+        assert_eq!(join(&b, &a).count(), 12 + 2);
+        assert_eq!(join(&a, &RTree::<i32, ()>::new()).count(), 0);
+    }
+}
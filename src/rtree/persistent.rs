@@ -0,0 +1,345 @@
+//! A persistent variant of the `RTree`, where each modification returns a new version of the tree
+//! sharing its unchanged subtrees with the previous versions.
+//!
+//! Nodes are reference counted, and a modification only copies the nodes along the paths it
+//! changes. Keeping every version of a map then costs memory proportional to the changes.
+use std::fmt::{
+    self,
+    Debug,
+};
+use std::ops::{
+    Deref,
+    DerefMut,
+};
+use std::sync::Arc;
+
+use ::geometry::{
+    Coordinate,
+    Point,
+    Tile,
+};
+use super::{
+    Child,
+    Intersecting,
+    Leaves,
+    Node,
+    RTree,
+    SplitStrategy,
+    Summary,
+};
+
+/// The handle of a child shared between the versions of a tree. Dereferencing it mutably copies
+/// the child first if another version holds it, so the node algorithms of the `RTree` only copy
+/// the nodes they change.
+struct Shared<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>>(Arc<Node<Coord, Value, S, Shared<Coord, Value, S>>>);
+
+impl<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> Clone for Shared<Coord, Value, S> {
+    fn clone(&self) -> Shared<Coord, Value, S> {
+        Shared(self.0.clone())
+    }
+}
+
+impl<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> Debug for Shared<Coord, Value, S> {
+    fn fmt(&self, fmt : &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+impl<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>> Deref for Shared<Coord, Value, S> {
+    type Target = Node<Coord, Value, S, Shared<Coord, Value, S>>;
+
+    fn deref(&self) -> &Node<Coord, Value, S, Shared<Coord, Value, S>> {
+        &self.0
+    }
+}
+
+impl<Coord : Coordinate, Value : Debug + Clone, S : Summary<Coord, Value>> DerefMut for Shared<Coord, Value, S> {
+    fn deref_mut(&mut self) -> &mut Node<Coord, Value, S, Shared<Coord, Value, S>> {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+impl<Coord : Coordinate, Value : Debug + Clone, S : Summary<Coord, Value>> Child<Coord, Value, S> for Shared<Coord, Value, S> {
+
+    fn new(node : Node<Coord, Value, S, Shared<Coord, Value, S>>) -> Shared<Coord, Value, S> {
+        Shared(Arc::new(node))
+    }
+
+    /// Unwraps the node, copying it if another version holds it
+    fn into_node(self) -> Node<Coord, Value, S, Shared<Coord, Value, S>> {
+        Arc::try_unwrap(self.0).unwrap_or_else(|node| (*node).clone())
+    }
+}
+
+/// A persistent `RTree` : `insert` and `remove` leave the tree unchanged and return a new version.
+/// Cloning a version is cheap, and versions share their unchanged subtrees. Internal nodes cache
+/// the summary `S` of their subtree, see `Summary`.
+#[derive(Debug, Clone)]
+pub struct PersistentRTree<Coord : Coordinate, Data : Debug + Clone, S : Summary<Coord, Data> = ()> {
+    fill_factor : usize,
+    split : Arc<SplitStrategy<Coord>>,
+    len : usize,
+    root : Option<Shared<Coord, Data, S>>,
+}
+
+impl<Coord : Coordinate, Data : Debug + Clone> PersistentRTree<Coord, Data> {
+
+    /// Creates a new empty tree
+    pub fn new() -> PersistentRTree<Coord, Data> {
+        PersistentRTree::from(RTree::new())
+    }
+
+    /// Creates a new empty tree with an user defined radix
+    pub fn with_radix(radix : usize) -> PersistentRTree<Coord, Data> {
+        PersistentRTree::from(RTree::with_radix(radix))
+    }
+}
+
+impl<Coord : Coordinate, Data : Debug + Clone, S : Summary<Coord, Data>> PersistentRTree<Coord, Data, S> {
+
+    /// Returns a new version of the tree with the point inserted, and the old value associated to
+    /// this point
+    pub fn insert(&self, point : Point<Coord>, data : Data) -> (PersistentRTree<Coord, Data, S>, Option<Data>) {
+        self.insert_tile(Tile::from_point(point), data)
+    }
+
+    /// Returns a new version of the tree with the tile shaped object inserted, and the old value
+    /// associated to this tile
+    pub fn insert_tile(&self, tile : Tile<Coord>, data : Data) -> (PersistentRTree<Coord, Data, S>, Option<Data>) {
+        let mut tree = self.clone();
+        let root = tree.root.take().map(Child::into_node);
+        let (root, ret_val) = Node::insert_root(root, tile, data, tree.fill_factor, &*tree.split);
+        tree.root = Some(Child::new(root));
+        if ret_val.is_none() {
+            tree.len += 1;
+        }
+        (tree, ret_val)
+    }
+
+    /// Returns a new version of the tree without the point, and the value associated to this
+    /// point or `None` if the point is not in the tree
+    pub fn remove(&self, point : Point<Coord>) -> (PersistentRTree<Coord, Data, S>, Option<Data>) {
+        self.remove_tile(&Tile::from_point(point))
+    }
+
+    /// Returns a new version of the tree without the object, and the value associated to this
+    /// tile or `None` if the object is not in the tree. The tree is left shared with this version
+    /// if the object is not in it.
+    pub fn remove_tile(&self, tile : &Tile<Coord>) -> (PersistentRTree<Coord, Data, S>, Option<Data>) {
+        let mut tree = self.clone();
+        let ret_val =
+            match tree.root.take() {
+                Some(root) => if root.contains_part(tile) {
+                    let (root, ret_val) = Node::remove_root(root.into_node(), tile, tree.fill_factor, &*tree.split);
+                    tree.root = root.map(Child::new);
+                    ret_val
+                } else {
+                    tree.root = Some(root);
+                    None
+                },
+                None => None,
+            };
+        if ret_val.is_some() {
+            tree.len -= 1;
+        }
+        (tree, ret_val)
+    }
+
+    /// Returns the value associated to a point
+    pub fn find(&self, point : Point<Coord>) -> Option<&Data> {
+        self.find_tile(&Tile::from_point(point))
+    }
+
+    /// Returns the value associated to an object
+    pub fn find_tile(&self, tile : &Tile<Coord>) -> Option<&Data> {
+        self.root.as_ref().and_then(|root| root.find(tile))
+    }
+
+    /// Returns the number of objects in the tree
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the tree contains no object
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the objects of the tree and their associated values, in depth
+    /// first order of their leaves
+    pub fn iter<'a>(&'a self) -> PersistentIter<'a, Coord, Data, S> {
+        PersistentIter {
+            leaves : Leaves::new(self.root.as_deref()),
+        }
+    }
+
+    /// Returns a lazy iterator over all the objects intersecting the given tile and their
+    /// associated values, see `RTree::query_tile`
+    pub fn query_tile<'a>(&'a self, tile : &Tile<Coord>) -> PersistentQuery<'a, Coord, Data, S> {
+        PersistentQuery {
+            query : Intersecting::new(*tile, self.root.as_deref()),
+        }
+    }
+
+    /// Returns the combined summary of the objects whose bottom left corner is in the given tile,
+    /// see `RTree::aggregate`
+    pub fn aggregate(&self, tile : &Tile<Coord>) -> S {
+        self.root.as_ref().map(|root| root.aggregate(tile)).unwrap_or_else(S::empty)
+    }
+
+    /// Returns a mutable copy of the tree
+    pub fn to_rtree(&self) -> RTree<Coord, Data, S> {
+        RTree {
+            fill_factor : self.fill_factor,
+            split : self.split.clone(),
+            len : self.len,
+            root : self.root.clone().map(|root| root.into_node().convert()),
+        }
+    }
+}
+
+/// Moves a mutable tree into a first persistent version
+impl<Coord : Coordinate, Data : Debug + Clone, S : Summary<Coord, Data>> From<RTree<Coord, Data, S>> for PersistentRTree<Coord, Data, S> {
+
+    fn from(rtree : RTree<Coord, Data, S>) -> PersistentRTree<Coord, Data, S> {
+        PersistentRTree {
+            fill_factor : rtree.fill_factor,
+            split : rtree.split,
+            len : rtree.len,
+            root : rtree.root.map(|root| Child::new(root.convert())),
+        }
+    }
+}
+
+/// Creates an empty tree with the default configuration
+impl<Coord : Coordinate, Data : Debug + Clone, S : Summary<Coord, Data>> Default for PersistentRTree<Coord, Data, S> {
+
+    fn default() -> PersistentRTree<Coord, Data, S> {
+        PersistentRTree::from(RTree::default())
+    }
+}
+
+/// An iterator over the entries of a `PersistentRTree`, created by `PersistentRTree::iter`
+pub struct PersistentIter<'a, Coord : Coordinate + 'a, Data : Debug + Clone + 'a, S : Summary<Coord, Data> + 'a = ()> {
+    leaves : Leaves<'a, Coord, Data, S, Shared<Coord, Data, S>>,
+}
+
+impl<'a, Coord : Coordinate, Data : Debug + Clone, S : Summary<Coord, Data>> Iterator for PersistentIter<'a, Coord, Data, S> {
+    type Item = (Tile<Coord>, &'a Data);
+
+    fn next(&mut self) -> Option<(Tile<Coord>, &'a Data)> {
+        self.leaves.next()
+    }
+}
+
+/// An iterator over the objects intersecting a tile, created by `PersistentRTree::query_tile`
+pub struct PersistentQuery<'a, Coord : Coordinate + 'a, Data : Debug + Clone + 'a, S : Summary<Coord, Data> + 'a = ()> {
+    query : Intersecting<'a, Coord, Data, S, Shared<Coord, Data, S>>,
+}
+
+impl<'a, Coord : Coordinate, Data : Debug + Clone, S : Summary<Coord, Data>> Iterator for PersistentQuery<'a, Coord, Data, S> {
+    type Item = (Tile<Coord>, &'a Data);
+
+    fn next(&mut self) -> Option<(Tile<Coord>, &'a Data)> {
+        self.query.next()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use ::geometry::{Point, Tile};
+    use super::super::{Count, Node, RTreeBuilder};
+    use super::PersistentRTree;
+
+    fn entries(tree : &PersistentRTree<i32, u32>) -> Vec<((i32, i32), u32)> {
+        let mut entries = tree.iter().map(|(t, &d)| ((t.bottom_left_corner().get_x(), t.bottom_left_corner().get_y()), d)).collect::<Vec<_>>();
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn versions() {
+        let mut versions = vec![PersistentRTree::<i32, u32>::with_radix(3)];
+        for i in 0..150 {
+            let (x, y) = ((i * 7) % 23, (i * 11) % 17);
+            let last = versions.last().unwrap().clone();
+            let (tree, _) =
+                if i % 4 == 3 { last.remove(Point::new((i * 5) % 23, (i * 3) % 17)) } else { last.insert(Point::new(x, y), i as u32) };
+            versions.push(tree);
+        }
+
+        // Replay the operations on a mutable tree and compare every version
+        let mut rtree = ::rtree::RTree::<i32, u32>::with_radix(3);
+        for (i, version) in versions.iter().enumerate().skip(1) {
+            let i = (i - 1) as i32;
+            if i % 4 == 3 {
+                rtree.remove(Point::new((i * 5) % 23, (i * 3) % 17));
+            } else {
+                rtree.insert(Point::new((i * 7) % 23, (i * 11) % 17), i as u32);
+            }
+            let mut expected = rtree.iter().map(|(t, &d)| ((t.bottom_left_corner().get_x(), t.bottom_left_corner().get_y()), d)).collect::<Vec<_>>();
+            expected.sort();
+            assert_eq!(entries(version), expected);
+            assert_eq!(version.len(), rtree.len());
+            assert!(version.to_rtree().check_invariants().is_ok());
+        }
+        assert!(versions[0].is_empty());
+    }
+
+    #[test]
+    fn shared_subtrees() {
+        let points = (0..64).map(|i| (Point::new(i % 8, i / 8), i as u32)).collect();
+        let tree = PersistentRTree::from(::rtree::RTree::bulk_load(points));
+        let (updated, old) = tree.insert(Point::new(0, 0), 100);
+        assert_eq!(old, Some(0));
+        assert_eq!(tree.find(Point::new(0, 0)), Some(&0));
+        assert_eq!(updated.find(Point::new(0, 0)), Some(&100));
+
+        // Only the path to the point was copied
+        match (&**tree.root.as_ref().unwrap(), &**updated.root.as_ref().unwrap()) {
+            (&Node::Node { vector : ref v1, .. }, &Node::Node { vector : ref v2, .. }) => {
+                let shared = v1.iter().zip(v2).filter(|&(n1, n2)| Arc::ptr_eq(&n1.0, &n2.0)).count();
+                assert_eq!(shared, v1.len() - 1);
+            },
+            _ => panic!("the roots are internal nodes"),
+        }
+
+        let (removed, old) = updated.remove(Point::new(9, 9));
+        assert_eq!(old, None);
+        assert!(Arc::ptr_eq(&removed.root.as_ref().unwrap().0, &updated.root.as_ref().unwrap().0));
+    }
+
+    #[test]
+    fn tile_objects() {
+        let rug = Tile::new(Point::new(1, 1), Point::new(9, 3));
+        let mut tree = PersistentRTree::<i32, u32>::with_radix(2);
+        for i in 0..20 {
+            tree = tree.insert(Point::new(i % 10, i / 2), i as u32).0;
+        }
+        let (with_rug, _) = tree.insert_tile(rug, 99);
+        assert!(with_rug.to_rtree().check_invariants().is_ok());
+        assert_eq!(with_rug.find_tile(&rug), Some(&99));
+        assert_eq!(tree.find_tile(&rug), None);
+
+        let (without_rug, old) = with_rug.remove_tile(&rug);
+        assert_eq!(old, Some(99));
+        assert!(without_rug.to_rtree().check_invariants().is_ok());
+        assert_eq!(entries(&without_rug), entries(&tree));
+        assert_eq!(with_rug.len(), tree.len() + 1);
+    }
+
+    #[test]
+    fn summaries() {
+        let area = Tile::new(Point::new(0, 0), Point::new(4, 4));
+        let mut tree = PersistentRTree::from(RTreeBuilder::new().radix(3).build::<u32, Count>());
+        for i in 0..40 {
+            tree = tree.insert(Point::new(i % 8, i / 8), i as u32).0;
+        }
+        let (removed, _) = tree.remove(Point::new(2, 2));
+        assert_eq!(tree.aggregate(&area), Count(25));
+        assert_eq!(removed.aggregate(&area), Count(24));
+        assert_eq!(removed.to_rtree().aggregate(&area), Count(24));
+        assert!(removed.to_rtree().check_invariants().is_ok());
+    }
+}
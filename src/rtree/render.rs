@@ -0,0 +1,190 @@
+//! Graphviz and SVG renderings of the structure of an `RTree`, drawing node coverages as nested
+//! rectangles coloured by depth, points as dots and tile shaped objects as grey rectangles.
+use std::fmt::{
+    Debug,
+    Write,
+};
+
+use ::geometry::{
+    Coordinate,
+    Tile,
+};
+use super::{
+    Node,
+    Summary,
+};
+
+/// Fill colours of the node coverages, indexed by depth
+const COLOURS : [&'static str; 6] = ["#e8f0fe", "#c6dafc", "#a5c8f5", "#fde0c4", "#fbc4a0", "#f5a37c"];
+
+/// Size in pixels of the largest side of the SVG picture
+const SVG_SIZE : f64 = 800.0;
+
+/// Offset in pixels between a node rectangle and the rectangles of its children
+const SVG_INSET : f64 = 2.0;
+
+fn colour(depth : usize) -> &'static str {
+    COLOURS[depth % COLOURS.len()]
+}
+
+fn tile_label<Coord : Coordinate>(tile : &Tile<Coord>) -> String {
+    format!("{} - {}", tile.bottom_left_corner(), tile.top_right_corner())
+}
+
+/// Returns the label of an object, a single point for degenerated tiles
+fn object_label<Coord : Coordinate>(tile : &Tile<Coord>) -> String {
+    if tile.bottom_left_corner() == tile.top_right_corner() {
+        format!("{}", tile.bottom_left_corner())
+    } else {
+        tile_label(tile)
+    }
+}
+
+/// Returns the Graphviz description of a tree, each node being a cluster containing its children
+pub(super) fn dot<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>>(root : Option<&Node<Coord, Value, S>>) -> String {
+    let mut out = String::new();
+    let mut count = 0;
+    out.push_str("digraph rtree {\n");
+    out.push_str("    node [shape=point];\n");
+    if let Some(root) = root {
+        dot_node(root, 0, &mut count, &mut out);
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn dot_node<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>>(node : &Node<Coord, Value, S>, depth : usize, count : &mut usize, out : &mut String) {
+    let indent = "    ".repeat(depth + 1);
+    *count += 1;
+    match *node {
+        Node::Leaf { ref tile, .. } | Node::Fragment { ref tile, .. } => {
+            writeln!(out, "{}p{} [xlabel=\"{}\"];", indent, count, object_label(tile)).unwrap();
+        },
+        Node::Node { ref coverage, ref vector, .. } => {
+            writeln!(out, "{}subgraph cluster_{} {{", indent, count).unwrap();
+            writeln!(out, "{}    label=\"{}\"; style=filled; fillcolor=\"{}\";", indent, tile_label(coverage), colour(depth)).unwrap();
+            for child in vector {
+                dot_node(child, depth + 1, count, out);
+            }
+            writeln!(out, "{}}}", indent).unwrap();
+        },
+    }
+}
+
+/// Returns an SVG picture of a tree. Each point is the center of a unit cell, and the y axis goes
+/// up as in the game.
+pub(super) fn svg<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>>(root : Option<&Node<Coord, Value, S>>) -> String {
+    let mut out = String::new();
+    let bounds = root.map(|root| root.coverage());
+
+    let (width, height) = bounds.map_or((1.0, 1.0), |b| (b.width() as f64, b.height() as f64));
+    let scale = SVG_SIZE / width.max(height);
+    writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+             width * scale, height * scale).unwrap();
+
+    if let (Some(root), Some(bounds)) = (root, bounds) {
+        let svg = Svg { bounds : bounds, scale : scale, height : height };
+        svg.node(root, 0, &mut out);
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// The projection of the tree coordinates on the picture
+struct Svg<Coord : Coordinate> {
+    bounds : Tile<Coord>,
+    scale : f64,
+    /// Height of the picture in cells
+    height : f64,
+}
+
+impl<Coord : Coordinate> Svg<Coord> {
+
+    /// Returns the pixel coordinates of the top left corner of a cell. The offsets from the
+    /// origin are computed in `i128`, which holds them for any coordinate type up to 64 bits.
+    fn project(&self, x : Coord, y : Coord) -> (f64, f64) {
+        let origin = self.bounds.bottom_left_corner();
+        let x = (x.to_i128().unwrap() - origin.get_x().to_i128().unwrap()) as f64;
+        let y = (y.to_i128().unwrap() - origin.get_y().to_i128().unwrap()) as f64;
+        (x * self.scale, (self.height - y - 1.0) * self.scale)
+    }
+
+    fn node<Value : Debug, S : Summary<Coord, Value>>(&self, node : &Node<Coord, Value, S>, depth : usize, out : &mut String) {
+        match *node {
+            Node::Leaf { ref tile, .. } if tile.bottom_left_corner() == tile.top_right_corner() => {
+                let point = tile.bottom_left_corner();
+                let (x, y) = self.project(point.get_x(), point.get_y());
+                writeln!(out, "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"black\"><title>{}</title></circle>",
+                         x + self.scale / 2.0, y + self.scale / 2.0, (self.scale / 4.0).max(1.0), point).unwrap();
+            },
+            Node::Leaf { ref tile, ref clip, .. } | Node::Fragment { ref tile, ref clip } => {
+                // Only the part of the object stored in this leaf is drawn
+                let (bottom, top) = (clip.bottom_left_corner(), clip.top_right_corner());
+                let (left, upper) = self.project(bottom.get_x(), top.get_y());
+                writeln!(out, "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"grey\" fill-opacity=\"0.5\"><title>{}</title></rect>",
+                         left, upper, clip.width() as f64 * self.scale, clip.height() as f64 * self.scale, tile_label(tile)).unwrap();
+            },
+            Node::Node { ref coverage, ref vector, .. } => {
+                let (bottom, top) = (coverage.bottom_left_corner(), coverage.top_right_corner());
+                let (left, upper) = self.project(bottom.get_x(), top.get_y());
+                let inset = SVG_INSET * depth as f64;
+                let width = (coverage.width() as f64 * self.scale - 2.0 * inset).max(0.0);
+                let height = (coverage.height() as f64 * self.scale - 2.0 * inset).max(0.0);
+                writeln!(out, "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"black\"><title>{}</title></rect>",
+                         left + inset, upper + inset, width, height, colour(depth), tile_label(coverage)).unwrap();
+                for child in vector {
+                    self.node(child, depth + 1, out);
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ::geometry::Point;
+    use super::super::RTree;
+
+    fn rtree() -> RTree<i32, ()> {
+        let points = (0..4).flat_map(|x| (0..4).map(move |y| (Point::new(x * 2, y), ()))).collect();
+        RTree::bulk_load(points)
+    }
+
+    #[test]
+    fn dot() {
+        let dot = rtree().to_dot();
+        assert!(dot.starts_with("digraph rtree {\n"));
+        assert_eq!(dot.matches("subgraph cluster_").count(), 5);
+        assert_eq!(dot.matches("xlabel=").count(), 16);
+        assert!(dot.contains("label=\"0 0 - 6 3\""));
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+    }
+
+    #[test]
+    fn svg() {
+        let svg = rtree().to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"457.14"));
+        assert_eq!(svg.matches("<rect ").count(), 5);
+        assert_eq!(svg.matches("<circle ").count(), 16);
+        // The root covers the whole picture
+        assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"800\""));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn wide_tree() {
+        let mut rtree = RTree::<i32, ()>::new();
+        rtree.insert(Point::new(i32::min_value(), i32::min_value()), ());
+        rtree.insert(Point::new(i32::max_value(), i32::max_value()), ());
+        let svg = rtree.to_svg();
+        assert_eq!(svg.matches("<circle ").count(), 2);
+        assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"800\""));
+    }
+
+    #[test]
+    fn empty() {
+        let rtree = RTree::<i32, ()>::new();
+        assert_eq!(rtree.to_dot(), "digraph rtree {\n    node [shape=point];\n}\n");
+        assert_eq!(rtree.to_svg(), "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"800\">\n</svg>\n");
+    }
+}
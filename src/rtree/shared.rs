@@ -0,0 +1,192 @@
+//! An `RTree` shared between threads : readers work on snapshots while a single writer prepares
+//! the next version of the tree.
+use std::fmt::Debug;
+use std::sync::{
+    Mutex,
+    RwLock,
+};
+
+use ::geometry::{
+    Coordinate,
+    Point,
+    Tile,
+};
+use super::{
+    PersistentRTree,
+    RTree,
+};
+
+/// A tree shared between threads, publishing versions of a `PersistentRTree`.
+///
+/// Readers take a snapshot, which is a cheap clone of the current version, and query it without
+/// holding any lock. Writers are serialised : each one modifies a copy of the current version,
+/// sharing its unchanged subtrees, and publishes it when done. The lock on the current version
+/// is only held to clone or replace it, so readers never wait for a modification to complete.
+#[derive(Debug)]
+pub struct SharedRTree<Coord : Coordinate, Data : Debug + Clone> {
+    current : RwLock<PersistentRTree<Coord, Data>>,
+    /// Held by the writer preparing the next version
+    writer : Mutex<()>,
+}
+
+impl<Coord : Coordinate, Data : Debug + Clone> SharedRTree<Coord, Data> {
+
+    /// Creates a new empty shared tree
+    pub fn new() -> SharedRTree<Coord, Data> {
+        SharedRTree::from(PersistentRTree::new())
+    }
+
+    /// Returns the current version of the tree. Later modifications are not visible in the
+    /// snapshot.
+    pub fn snapshot(&self) -> PersistentRTree<Coord, Data> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Calls `f` on a copy of the current version of the tree, then publishes the modified copy.
+    /// Writers are serialised, so no modification is lost. Returns the result of `f`.
+    pub fn write<R, F : FnOnce(&mut PersistentRTree<Coord, Data>) -> R>(&self, f : F) -> R {
+        let _writer = self.writer.lock().unwrap();
+        let mut tree = self.snapshot();
+        let result = f(&mut tree);
+        *self.current.write().unwrap() = tree;
+        result
+    }
+
+    /// Inserts a point and returns the old value associated to this point
+    pub fn insert(&self, point : Point<Coord>, data : Data) -> Option<Data> {
+        self.insert_tile(Tile::from_point(point), data)
+    }
+
+    /// Inserts a tile shaped object and returns the old value associated to this tile
+    pub fn insert_tile(&self, tile : Tile<Coord>, data : Data) -> Option<Data> {
+        self.write(|tree| {
+            let (next, old) = tree.insert_tile(tile, data);
+            *tree = next;
+            old
+        })
+    }
+
+    /// Removes a point and returns the value associated to this point, or `None` if the point is
+    /// not in the tree
+    pub fn remove(&self, point : Point<Coord>) -> Option<Data> {
+        self.remove_tile(&Tile::from_point(point))
+    }
+
+    /// Removes an object and returns the value associated to this tile, or `None` if the object
+    /// is not in the tree
+    pub fn remove_tile(&self, tile : &Tile<Coord>) -> Option<Data> {
+        self.write(|tree| {
+            let (next, old) = tree.remove_tile(tile);
+            *tree = next;
+            old
+        })
+    }
+
+    /// Returns a copy of the value associated to a point in the current version
+    pub fn find(&self, point : Point<Coord>) -> Option<Data> {
+        self.snapshot().find(point).cloned()
+    }
+
+    /// Returns the number of objects in the current version
+    pub fn len(&self) -> usize {
+        self.current.read().unwrap().len()
+    }
+
+    /// Returns true if the current version contains no object
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Shares a first version of the tree
+impl<Coord : Coordinate, Data : Debug + Clone> From<PersistentRTree<Coord, Data>> for SharedRTree<Coord, Data> {
+
+    fn from(tree : PersistentRTree<Coord, Data>) -> SharedRTree<Coord, Data> {
+        SharedRTree {
+            current : RwLock::new(tree),
+            writer : Mutex::new(()),
+        }
+    }
+}
+
+/// Shares a copy of a mutable tree
+impl<Coord : Coordinate, Data : Debug + Clone> From<RTree<Coord, Data>> for SharedRTree<Coord, Data> {
+
+    fn from(tree : RTree<Coord, Data>) -> SharedRTree<Coord, Data> {
+        SharedRTree::from(PersistentRTree::from(tree))
+    }
+}
+
+/// Shares an empty tree with the default configuration
+impl<Coord : Coordinate, Data : Debug + Clone> Default for SharedRTree<Coord, Data> {
+
+    fn default() -> SharedRTree<Coord, Data> {
+        SharedRTree::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::thread;
+    use ::geometry::{Point, Tile};
+    use super::SharedRTree;
+
+    #[test]
+    fn readers_and_writers() {
+        let shared = Arc::new(SharedRTree::<i32, u32>::new());
+        for i in 0..50 {
+            shared.insert(Point::new(i % 10, i / 10), 1);
+        }
+        let area = Tile::new(Point::new(0, 0), Point::new(9, 9));
+
+        // Rovers cleaning the dust, each in its own column
+        let writers = (0..4).map(|x| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                for y in 0..5 {
+                    assert_eq!(shared.remove(Point::new(x, y)), Some(1));
+                }
+            })
+        }).collect::<Vec<_>>();
+        let readers = (0..4).map(|_| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    // A snapshot is consistent even if the tree is modified meanwhile
+                    let snapshot = shared.snapshot();
+                    assert_eq!(snapshot.query_tile(&area).count(), snapshot.len());
+                    assert!(snapshot.len() >= 30);
+                }
+            })
+        }).collect::<Vec<_>>();
+
+        for handle in writers.into_iter().chain(readers) {
+            handle.join().unwrap();
+        }
+        assert_eq!(shared.len(), 30);
+        assert_eq!(shared.find(Point::new(3, 4)), None);
+        assert_eq!(shared.find(Point::new(4, 4)), Some(1));
+        assert!(shared.snapshot().to_rtree().check_invariants().is_ok());
+    }
+
+    #[test]
+    fn snapshots() {
+        let shared = SharedRTree::<i32, u32>::new();
+        shared.insert(Point::new(1, 1), 1);
+        let before = shared.snapshot();
+        let old = shared.write(|tree| {
+            *tree = tree.insert(Point::new(2, 2), 2).0;
+            let (next, old) = tree.insert(Point::new(1, 1), 3);
+            *tree = next;
+            old
+        });
+
+        assert_eq!(old, Some(1));
+        assert_eq!(before.len(), 1);
+        assert_eq!(before.find(Point::new(1, 1)), Some(&1));
+        assert_eq!(shared.len(), 2);
+        assert_eq!(shared.find(Point::new(1, 1)), Some(3));
+        assert!(!shared.is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+//! Strategies computing the line splitting an overflowing node.
+//!
+//! All the strategies choose an axis aligned line. Children starting before or on the line go to
+//! the first node, children crossing the line are recursively split and the others go to the
+//! second node. Strategies only differ by the cost they minimise.
+use std::cmp::{
+    Ordering,
+    max,
+    min,
+};
+use std::fmt::Debug;
+
+use ::geometry::{
+    Coordinate,
+    Point,
+    Tile,
+    Line,
+    VerticalLine,
+    HorizontalLine,
+};
+
+/// A strategy computing the line splitting the children of an overflowing node. Strategies are
+/// shared between the versions of a tree, possibly across threads.
+pub trait SplitStrategy<Coord : Coordinate> : Debug + Send + Sync {
+
+    /// Returns the line splitting the children `tiles` of a node into two nodes, putting at most
+    /// `fill_factor` children into the first one and less children than `tiles` into the second
+    /// one. Returns `None` if no such line exists, when the objects stored in the node overlap too
+    /// much.
+    ///
+    /// # Panics
+    ///
+    /// Panics when there are at most `fill_factor` tiles
+    fn split(&self, tiles : &[Tile<Coord>], fill_factor : usize) -> Option<Box<Line<Coord>>>;
+}
+
+/// The greedy sweep : fills the first node as much as possible and picks the line crossing the
+/// fewest tiles, to minimise the height of the tree.
+///
+/// This differs from the sweep the tree used before the strategies were introduced, which put the
+/// line at the start of the `fill_factor`-th tile on each axis and kept the axis crossing fewer
+/// tiles. That line could leave more than `fill_factor` tiles in the first node when tiles start
+/// on it, and no line was rejected. Every line along a tile border is now scored, lines leaving
+/// the second node overflowing are only picked when no other exists, and on equal crossings the
+/// fuller first node wins. `None` is returned when no line separates the tiles.
+#[derive(Debug, Clone, Copy)]
+pub struct GreedySweep;
+
+/// The median split : among the lines crossing the fewest tiles, picks the one leaving the same
+/// number of children in both nodes. This keeps the nodes half full on uniformly distributed points.
+#[derive(Debug, Clone, Copy)]
+pub struct MedianSplit;
+
+/// The cost based split : picks the line minimising the total area, then the total perimeter of
+/// the two nodes coverages. This produces tight nodes on clustered points.
+#[derive(Debug, Clone, Copy)]
+pub struct CostSplit;
+
+impl<Coord : Coordinate> SplitStrategy<Coord> for GreedySweep {
+
+    fn split(&self, tiles : &[Tile<Coord>], fill_factor : usize) -> Option<Box<Line<Coord>>> {
+        best_line(tiles, fill_factor, |c| (c.overflow(fill_factor), c.crossing, fill_factor - c.left))
+    }
+}
+
+impl<Coord : Coordinate> SplitStrategy<Coord> for MedianSplit {
+
+    fn split(&self, tiles : &[Tile<Coord>], fill_factor : usize) -> Option<Box<Line<Coord>>> {
+        best_line(tiles, fill_factor, |c| {
+            let imbalance = max(c.left, c.right) - min(c.left, c.right);
+            (c.overflow(fill_factor), c.crossing, imbalance)
+        })
+    }
+}
+
+impl<Coord : Coordinate> SplitStrategy<Coord> for CostSplit {
+
+    fn split(&self, tiles : &[Tile<Coord>], fill_factor : usize) -> Option<Box<Line<Coord>>> {
+        best_line(tiles, fill_factor, |c| {
+            let (left, right) = c.coverages(tiles);
+            (c.overflow(fill_factor), Cost(left.area() + right.area()), Cost(left.perimeter() + right.perimeter()))
+        })
+    }
+}
+
+/// A floating point cost, totally ordered by `f64::total_cmp`
+#[derive(Debug, Clone, Copy)]
+struct Cost(f64);
+
+impl PartialEq for Cost {
+    fn eq(&self, rhs : &Cost) -> bool {
+        self.cmp(rhs) == Ordering::Equal
+    }
+}
+
+impl Eq for Cost { }
+
+impl PartialOrd for Cost {
+    fn partial_cmp(&self, rhs : &Cost) -> Option<Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl Ord for Cost {
+    fn cmp(&self, rhs : &Cost) -> Ordering {
+        self.0.total_cmp(&rhs.0)
+    }
+}
+
+/// A candidate splitting line
+struct Candidate<Coord : Coordinate> {
+    vertical : bool,
+    position : Coord,
+    /// Number of tiles in the first node
+    left : usize,
+    /// Number of tiles in the second node
+    right : usize,
+    /// Number of tiles crossing the line, which are in both nodes
+    crossing : usize,
+}
+
+impl<Coord : Coordinate> Candidate<Coord> {
+
+    /// Returns the number of tiles exceeding the fill factor in the second node
+    fn overflow(&self, fill_factor : usize) -> usize {
+        self.right.saturating_sub(fill_factor)
+    }
+
+    /// Returns the coverages of the two nodes, crossing tiles being clipped by the line
+    fn coverages(&self, tiles : &[Tile<Coord>]) -> (Tile<Coord>, Tile<Coord>) {
+        let axis = |p : Point<Coord>| if self.vertical { p.get_x() } else { p.get_y() };
+        let clip = |p : Point<Coord>, v : Coord| if self.vertical { Point::new(v, p.get_y()) } else { Point::new(p.get_x(), v) };
+
+        let mut left : Option<Tile<Coord>> = None;
+        let mut right : Option<Tile<Coord>> = None;
+        for tile in tiles {
+            let (bottom, top) = (tile.bottom_left_corner(), tile.top_right_corner());
+            if axis(bottom) <= self.position {
+                let part = Tile::new(bottom, clip(top, min(axis(top), self.position)));
+                left = Some(left.map_or(part, |t| t.union(part)));
+            }
+            if axis(top) > self.position {
+                let part = Tile::new(clip(bottom, max(axis(bottom), self.position + Coord::one())), top);
+                right = Some(right.map_or(part, |t| t.union(part)));
+            }
+        }
+        (left.unwrap(), right.unwrap())
+    }
+
+    fn line(&self) -> Box<Line<Coord>> {
+        if self.vertical {
+            Box::new(VerticalLine::new(self.position))
+        } else {
+            Box::new(HorizontalLine::new(self.position))
+        }
+    }
+}
+
+/// Returns the candidate lines along one axis, `axis` returning the coordinate of a point on this
+/// axis. Candidates are the lines passing by a tile border, leaving between one and `fill_factor`
+/// tiles in the first node and at least one tile out of the second node.
+fn axis_candidates<Coord, F>(tiles : &[Tile<Coord>], fill_factor : usize, vertical : bool, axis : F) -> Vec<Candidate<Coord>>
+where Coord : Coordinate, F : Fn(Point<Coord>) -> Coord
+{
+    let mut starts = tiles.iter().map(|t| axis(t.bottom_left_corner())).collect::<Vec<_>>();
+    let ends = tiles.iter().map(|t| axis(t.top_right_corner())).collect::<Vec<_>>();
+    starts.sort();
+
+    // The first tiles fill the first node, the line has to be before the start of the next one
+    let limit = starts[fill_factor];
+
+    starts.iter()
+        .chain(ends.iter())
+        .cloned()
+        .filter(|&line| line < limit && line >= starts[0])
+        .map(|line| {
+            let left = starts.iter().filter(|&&start| start <= line).count();
+            let right = ends.iter().filter(|&&end| end > line).count();
+            Candidate {
+                vertical : vertical,
+                position : line,
+                left : left,
+                right : right,
+                crossing : left + right - tiles.len(),
+            }
+        })
+        .filter(|c| c.right < tiles.len())
+        .collect()
+}
+
+/// Returns the candidate line with the lowest cost. Vertical lines come first on equal costs.
+fn best_line<Coord, K, F>(tiles : &[Tile<Coord>], fill_factor : usize, cost : F) -> Option<Box<Line<Coord>>>
+where Coord : Coordinate, K : Ord, F : Fn(&Candidate<Coord>) -> K
+{
+    let mut candidates = axis_candidates(tiles, fill_factor, true, |p| p.get_x());
+    candidates.extend(axis_candidates(tiles, fill_factor, false, |p| p.get_y()));
+
+    candidates.iter()
+        .min_by_key(|c| cost(c))
+        .map(|c| c.line())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::geometry::{Point, Tile, Side, SideOf};
+
+    fn tiles() -> Vec<Tile<u16>> {
+        vec![Tile::new(Point::new(0, 0), Point::new(1, 1)),
+        Tile::new(Point::new(2, 0), Point::new(3, 1)),
+        Tile::new(Point::new(4, 0), Point::new(5, 1)),
+        Tile::new(Point::new(0, 8), Point::new(1, 9)),
+        Tile::new(Point::new(20, 8), Point::new(21, 9)),
+        ]
+    }
+
+    #[test]
+    fn greedy_fills_first_node() {
+        let line = GreedySweep.split(&tiles(), 4).unwrap();
+        assert!(line.is_vertical());
+        assert!(tiles()[..4].iter().all(|t| t.side_of(&*line) != Side::After));
+        assert_eq!(tiles()[4].side_of(&*line), Side::After);
+    }
+
+    #[test]
+    fn median_balances_nodes() {
+        let line = MedianSplit.split(&tiles(), 4).unwrap();
+        assert!(line.is_vertical());
+        assert_eq!(tiles().iter().filter(|t| t.side_of(&*line) != Side::After).count(), 2);
+        assert_eq!(tiles().iter().filter(|t| t.side_of(&*line) == Side::After).count(), 3);
+    }
+
+    #[test]
+    fn cost_minimises_area() {
+        let line = CostSplit.split(&tiles(), 4).unwrap();
+        assert!(line.is_horizontal());
+        assert!(tiles()[..3].iter().all(|t| t.side_of(&*line) != Side::After));
+        assert!(tiles()[3..].iter().all(|t| t.side_of(&*line) == Side::After));
+    }
+
+    #[test]
+    fn overlapping_tiles() {
+        let tiles = vec![Tile::new(Point::new(0u16, 0), Point::new(1, 1)); 3];
+        assert!(CostSplit.split(&tiles, 2).is_none());
+
+        // Every line leaves all the tiles in the second node
+        let tiles = vec![Tile::new(Point::new(0u16, 0), Point::new(9, 9)),
+        Tile::new(Point::new(1, 1), Point::new(9, 9)),
+        Tile::new(Point::new(2, 2), Point::new(9, 9))];
+        assert!(GreedySweep.split(&tiles, 2).is_none());
+    }
+}
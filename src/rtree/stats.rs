@@ -0,0 +1,169 @@
+//! Structural statistics of an `RTree`, to detect degenerated trees.
+use std::fmt::{
+    self,
+    Debug,
+    Display,
+};
+
+use ::geometry::{
+    Coordinate,
+    Tile,
+};
+use super::{
+    Node,
+    Summary,
+};
+
+/// Statistics about the structure of an `RTree`, returned by `RTree::stats`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeStats {
+    /// Number of levels of the tree, a single object has a depth of 1
+    pub depth : usize,
+    /// Number of internal nodes
+    pub internal_nodes : usize,
+    /// Number of internal nodes with a single child
+    pub single_child_nodes : usize,
+    /// Number of leaves, which is the number of objects
+    pub leaves : usize,
+    /// Number of parts of clipped objects stored outside of their leaf
+    pub fragments : usize,
+    /// Average number of children of the internal nodes
+    pub average_fanout : f64,
+    /// Average ratio of used child slots of the internal nodes of each level, starting from the
+    /// root
+    pub fill_ratio : Vec<f64>,
+    /// Sum of the areas of the internal nodes coverages
+    pub coverage_area : f64,
+    /// Sum of the areas of the intersections between sibling coverages
+    pub overlap_area : f64,
+}
+
+/// Returns the area of the intersection of two tiles
+fn overlap_area<Coord : Coordinate>(t1 : &Tile<Coord>, t2 : &Tile<Coord>) -> f64 {
+    t1.intersection(t2).map_or(0.0, |common| common.area())
+}
+
+impl TreeStats {
+
+    /// Computes the statistics of a tree
+    pub(super) fn new<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>>(root : Option<&Node<Coord, Value, S>>, fill_factor : usize) -> TreeStats {
+        let mut stats = TreeStats {
+            depth : 0,
+            internal_nodes : 0,
+            single_child_nodes : 0,
+            leaves : 0,
+            fragments : 0,
+            average_fanout : 0.0,
+            fill_ratio : Vec::new(),
+            coverage_area : 0.0,
+            overlap_area : 0.0,
+        };
+        // Number of internal nodes and children at each level
+        let mut levels = Vec::new();
+
+        if let Some(root) = root {
+            stats.visit(root, 0, &mut levels);
+        }
+
+        let children = levels.iter().map(|&(_, children)| children).sum::<usize>();
+        if stats.internal_nodes > 0 {
+            stats.average_fanout = children as f64 / stats.internal_nodes as f64;
+        }
+        stats.fill_ratio =
+            levels.iter()
+            .map(|&(nodes, children)| children as f64 / (nodes * fill_factor) as f64)
+            .collect();
+        stats
+    }
+
+    fn visit<Coord : Coordinate, Value : Debug, S : Summary<Coord, Value>>(&mut self, node : &Node<Coord, Value, S>, level : usize, levels : &mut Vec<(usize, usize)>) {
+        if self.depth <= level {
+            self.depth = level + 1;
+        }
+        match *node {
+            Node::Leaf { .. } => self.leaves += 1,
+            Node::Fragment { .. } => self.fragments += 1,
+            Node::Node { ref coverage, ref vector, .. } =>
+            {
+                self.internal_nodes += 1;
+                if vector.len() == 1 {
+                    self.single_child_nodes += 1;
+                }
+                self.coverage_area += coverage.area();
+
+                if levels.len() <= level {
+                    levels.push((0, 0));
+                }
+                levels[level].0 += 1;
+                levels[level].1 += vector.len();
+
+                for (i, child) in vector.iter().enumerate() {
+                    for sibling in &vector[i + 1..] {
+                        self.overlap_area += overlap_area(&child.coverage(), &sibling.coverage());
+                    }
+                    self.visit(child, level + 1, levels);
+                }
+            }
+        }
+    }
+}
+
+impl Display for TreeStats {
+    fn fmt(&self, fmt : &mut fmt::Formatter) -> fmt::Result {
+        try!(writeln!(fmt, "depth : {}", self.depth));
+        try!(writeln!(fmt, "internal nodes : {} ({} with a single child)", self.internal_nodes, self.single_child_nodes));
+        try!(writeln!(fmt, "leaves : {} ({} fragments)", self.leaves, self.fragments));
+        try!(writeln!(fmt, "average fanout : {:.2}", self.average_fanout));
+        for (level, ratio) in self.fill_ratio.iter().enumerate() {
+            try!(writeln!(fmt, "fill ratio of level {} : {:.2}", level, ratio));
+        }
+        write!(fmt, "coverage area : {}, overlapping area : {}", self.coverage_area, self.overlap_area)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ::geometry::{Point, Tile};
+    use super::super::RTree;
+
+    #[test]
+    fn packed_tree() {
+        let points = (0..4).flat_map(|x| (0..4).map(move |y| (Point::new(x * 2, y), ()))).collect();
+        let stats = RTree::<i32, ()>::bulk_load(points).stats();
+
+        assert_eq!(stats.depth, 3);
+        assert_eq!(stats.internal_nodes, 5);
+        assert_eq!(stats.single_child_nodes, 0);
+        assert_eq!(stats.leaves, 16);
+        assert_eq!(stats.average_fanout, 4.0);
+        assert_eq!(stats.fill_ratio, vec![1.0, 1.0]);
+        // The root covers 7x4 cells and each child a 3x2 block holding four points
+        assert_eq!(stats.coverage_area, 28.0 + 4.0 * 6.0);
+        assert_eq!(stats.overlap_area, 0.0);
+    }
+
+    #[test]
+    fn small_trees() {
+        let mut rtree = RTree::<i32, ()>::new();
+        assert_eq!(rtree.stats().depth, 0);
+        assert_eq!(rtree.stats().fill_ratio, Vec::<f64>::new());
+
+        rtree.insert(Point::new(1, 1), ());
+        let stats = rtree.stats();
+        assert_eq!((stats.depth, stats.internal_nodes, stats.leaves), (1, 0, 1));
+
+        rtree.insert(Point::new(3, 5), ());
+        let stats = rtree.stats();
+        assert_eq!((stats.depth, stats.internal_nodes, stats.leaves), (2, 1, 2));
+        assert_eq!(stats.fill_ratio, vec![0.5]);
+        assert_eq!(stats.coverage_area, 15.0);
+    }
+
+    #[test]
+    fn overlap() {
+        let t1 = Tile::new(Point::new(0, 0), Point::new(4, 4));
+        let t2 = Tile::new(Point::new(2, 3), Point::new(6, 6));
+        assert_eq!(super::overlap_area(&t1, &t2), 6.0);
+        assert_eq!(super::overlap_area(&t1, &Tile::new(Point::new(5, 5), Point::new(6, 6))), 0.0);
+    }
+}
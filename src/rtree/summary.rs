@@ -0,0 +1,153 @@
+//! Summaries of the objects of a subtree, cached on the internal nodes of an `RTree` to aggregate
+//! the values of a tile without visiting every object.
+use std::fmt::Debug;
+
+use ::geometry::{
+    Coordinate,
+    Tile,
+};
+
+/// A monoid summarising a set of objects. Each internal node caches the summary of its subtree,
+/// which `RTree::aggregate` combines.
+///
+/// `combine` must be associative and `empty` must be its identity, since the summaries are
+/// combined in the order of the tree nodes.
+pub trait Summary<Coord : Coordinate, Data> : Debug + Clone {
+
+    /// Returns the summary of no object
+    fn empty() -> Self;
+
+    /// Returns the summary of a single object
+    fn of(tile : &Tile<Coord>, data : &Data) -> Self;
+
+    /// Returns the summary of the union of two sets of objects
+    fn combine(&self, other : &Self) -> Self;
+}
+
+/// No summary, the default for trees which are never aggregated
+impl<Coord : Coordinate, Data> Summary<Coord, Data> for () {
+
+    fn empty() { }
+
+    fn of(_ : &Tile<Coord>, _ : &Data) { }
+
+    fn combine(&self, _ : &()) { }
+}
+
+/// The number of objects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Count(pub usize);
+
+impl<Coord : Coordinate, Data> Summary<Coord, Data> for Count {
+
+    fn empty() -> Count {
+        Count(0)
+    }
+
+    fn of(_ : &Tile<Coord>, _ : &Data) -> Count {
+        Count(1)
+    }
+
+    fn combine(&self, other : &Count) -> Count {
+        Count(self.0 + other.0)
+    }
+}
+
+/// Two summaries computed together
+impl<Coord : Coordinate, Data, A, B> Summary<Coord, Data> for (A, B)
+where A : Summary<Coord, Data>, B : Summary<Coord, Data>
+{
+
+    fn empty() -> (A, B) {
+        (A::empty(), B::empty())
+    }
+
+    fn of(tile : &Tile<Coord>, data : &Data) -> (A, B) {
+        (A::of(tile, data), B::of(tile, data))
+    }
+
+    fn combine(&self, other : &(A, B)) -> (A, B) {
+        (self.0.combine(&other.0), self.1.combine(&other.1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ::geometry::{Point, Tile, Contains};
+    use super::super::{RTree, RTreeBuilder};
+    use super::{Count, Summary};
+
+    /// The total dust weight and the highest priority
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Dust {
+        weight : u64,
+        priority : Option<u32>,
+    }
+
+    impl Summary<i32, (u32, u32)> for Dust {
+
+        fn empty() -> Dust {
+            Dust { weight : 0, priority : None }
+        }
+
+        fn of(_ : &Tile<i32>, &(weight, priority) : &(u32, u32)) -> Dust {
+            Dust { weight : weight as u64, priority : Some(priority) }
+        }
+
+        fn combine(&self, other : &Dust) -> Dust {
+            Dust { weight : self.weight + other.weight, priority : self.priority.max(other.priority) }
+        }
+    }
+
+    /// Aggregates the objects of the tree one by one
+    fn expected<S : Summary<i32, (u32, u32)>>(rtree : &RTree<i32, (u32, u32), S>, tile : &Tile<i32>) -> S {
+        rtree.iter()
+            .filter(|&(t, _)| tile.contains(&t.bottom_left_corner()))
+            .fold(S::empty(), |s, (t, d)| s.combine(&S::of(&t, d)))
+    }
+
+    #[test]
+    fn aggregate() {
+        let mut rtree = RTreeBuilder::new().radix(3).build::<(u32, u32), (Count, Dust)>();
+        let queries = [Tile::new(Point::new(0, 0), Point::new(40, 40)),
+                       Tile::new(Point::new(3, 4), Point::new(11, 9)),
+                       Tile::new(Point::new(-5, 10), Point::new(2, 30)),
+                       Tile::from_point(Point::new(7, 7))];
+
+        for i in 0..200 {
+            let (x, y) = ((i * 7) % 31, (i * 11) % 23);
+            if i % 5 == 0 {
+                let tile = Tile::new(Point::new(x, y), Point::new(x + i % 4, y + i % 3));
+                rtree.insert_tile(tile, (i as u32, (i % 17) as u32));
+            } else {
+                rtree.insert(Point::new(x, y), (i as u32, (i % 13) as u32));
+            }
+            if i % 7 == 3 {
+                rtree.remove(Point::new((i * 3) % 31, (i * 5) % 23));
+            }
+            if i % 4 == 1 {
+                rtree.update(&Tile::from_point(Point::new(x, y)), |d| d.0 *= 2);
+            }
+            for tile in &queries {
+                let (count, dust) = rtree.aggregate(tile);
+                assert_eq!((count, dust), expected(&rtree, tile));
+            }
+        }
+        assert_eq!(rtree.aggregate(&queries[0]).0, Count(rtree.len()));
+        assert!(rtree.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn update() {
+        let mut rtree = RTreeBuilder::new().build::<(u32, u32), Dust>();
+        let rug = Tile::new(Point::new(1, 1), Point::new(4, 2));
+        rtree.insert_tile(rug, (5, 1));
+        rtree.insert(Point::new(2, 2), (1, 3));
+
+        assert_eq!(rtree.update(&rug, |d| { d.0 += 10; d.1 }), Some(1));
+        assert_eq!(rtree.update(&Tile::from_point(Point::new(9, 9)), |d| d.0), None);
+        assert_eq!(rtree.aggregate(&Tile::new(Point::new(0, 0), Point::new(2, 2))), Dust { weight : 16, priority : Some(3) });
+        assert_eq!(rtree.aggregate(&Tile::new(Point::new(2, 1), Point::new(9, 9))), Dust { weight : 1, priority : Some(3) });
+        assert_eq!(RTree::<i32, (u32, u32)>::new().aggregate(&rug), ());
+    }
+}